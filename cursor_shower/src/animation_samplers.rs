@@ -0,0 +1,848 @@
+//! Samplers for shaping values over time.
+
+use raylib::ease::{self, EaseFn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    linear_samplers::{child, Child, Movesampler1D, Sampler1D, SamplerData, Time},
+    logic_samplers::is_true,
+};
+
+/// The easing families from raylib's `ease` module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum EaseCurve {
+    Linear,
+    Sine,
+    Circ,
+    Quad,
+    Cubic,
+    Expo,
+    Back,
+    Bounce,
+    Elastic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum EaseMode {
+    In,
+    Out,
+    InOut,
+}
+
+fn ease_fn(curve: EaseCurve, mode: EaseMode) -> EaseFn {
+    use EaseCurve::*;
+    use EaseMode::*;
+    match (curve, mode) {
+        (Linear, _) => ease::linear_none,
+        (Sine, In) => ease::sine_in,
+        (Sine, Out) => ease::sine_out,
+        (Sine, InOut) => ease::sine_in_out,
+        (Circ, In) => ease::circ_in,
+        (Circ, Out) => ease::circ_out,
+        (Circ, InOut) => ease::circ_in_out,
+        (Quad, In) => ease::quad_in,
+        (Quad, Out) => ease::quad_out,
+        (Quad, InOut) => ease::quad_in_out,
+        (Cubic, In) => ease::cubic_in,
+        (Cubic, Out) => ease::cubic_out,
+        (Cubic, InOut) => ease::cubic_in_out,
+        (Expo, In) => ease::expo_in,
+        (Expo, Out) => ease::expo_out,
+        (Expo, InOut) => ease::expo_in_out,
+        (Back, In) => ease::back_in,
+        (Back, Out) => ease::back_out,
+        (Back, InOut) => ease::back_in_out,
+        (Bounce, In) => ease::bounce_in,
+        (Bounce, Out) => ease::bounce_out,
+        (Bounce, InOut) => ease::bounce_in_out,
+        (Elastic, In) => ease::elastic_in,
+        (Elastic, Out) => ease::elastic_out,
+        (Elastic, InOut) => ease::elastic_in_out,
+    }
+}
+
+/// Eases `progress` from 0 to 1. Back and Elastic overshoot that range on the way.
+pub fn ease(curve: EaseCurve, mode: EaseMode, progress: f32) -> f32 {
+    ease_fn(curve, mode)(progress.clamp(0.0, 1.0), 0.0, 1.0, 1.0)
+}
+
+fn zero() -> f32 {
+    0.0
+}
+
+fn one() -> f32 {
+    1.0
+}
+
+/// Like `Map`, but follows an easing curve instead of a straight line, and holds the ends of the
+/// output range instead of extrapolating past them. The ranges default to 0 to 1.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Ease {
+    pub value: Box<Movesampler1D>,
+    pub curve: EaseCurve,
+    pub mode: EaseMode,
+    #[serde(default = "zero")]
+    pub before_min: f32,
+    #[serde(default = "one")]
+    pub before_max: f32,
+    #[serde(default = "zero")]
+    pub after_min: f32,
+    #[serde(default = "one")]
+    pub after_max: f32,
+}
+
+impl Sampler1D for Ease {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let value = self.value.sample(data);
+        let progress = (value - self.before_min) / (self.before_max - self.before_min);
+        let eased = ease(self.curve, self.mode, progress);
+        self.after_min + eased * (self.after_max - self.after_min)
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("value", &self.value)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.value]
+    }
+}
+
+impl From<Ease> for Movesampler1D {
+    fn from(e: Ease) -> Self {
+        Movesampler1D::Ease(e)
+    }
+}
+
+/// How a keyframe moves on to the next one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum Interpolation {
+    #[default]
+    Linear,
+    /// Holds the value until the next keyframe.
+    Step,
+    /// A smooth curve through the neighbouring keyframes (Catmull-Rom).
+    Cubic,
+    Ease {
+        curve: EaseCurve,
+        mode: EaseMode,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Keyframe {
+    pub time: f32,
+    pub value: f32,
+    #[serde(default)]
+    pub interpolation: Interpolation,
+}
+
+/// What happens once the input passes the last keyframe.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum Repeat {
+    /// Holds the first and last values.
+    #[default]
+    Clamp,
+    Loop,
+    /// Plays forwards, then backwards.
+    PingPong,
+}
+
+fn default_input() -> Box<Movesampler1D> {
+    Box::new(Movesampler1D::Time(Time { speed: 1.0 }))
+}
+
+/// Plays a list of keyframes against `input`, which is the time in seconds unless given.
+/// Keyframe times must increase.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Keyframes {
+    #[serde(default = "default_input")]
+    pub input: Box<Movesampler1D>,
+    pub keyframes: Vec<Keyframe>,
+    #[serde(default)]
+    pub repeat: Repeat,
+}
+
+impl Keyframes {
+    fn wrap(&self, input: f32) -> f32 {
+        let start = self.keyframes[0].time;
+        let span = self.keyframes[self.keyframes.len() - 1].time - start;
+        if span <= 0.0 {
+            return start;
+        }
+        match self.repeat {
+            Repeat::Clamp => input.clamp(start, start + span),
+            Repeat::Loop => start + (input - start).rem_euclid(span),
+            Repeat::PingPong => {
+                let phase = (input - start).rem_euclid(2.0 * span);
+                start
+                    + if phase > span {
+                        2.0 * span - phase
+                    } else {
+                        phase
+                    }
+            }
+        }
+    }
+
+    /// The slope at keyframe `i`, from its neighbours.
+    fn tangent(&self, i: usize) -> f32 {
+        let before = &self.keyframes[i.saturating_sub(1)];
+        let after = &self.keyframes[(i + 1).min(self.keyframes.len() - 1)];
+        if after.time > before.time {
+            (after.value - before.value) / (after.time - before.time)
+        } else {
+            0.0
+        }
+    }
+
+    pub fn value_at(&self, input: f32) -> f32 {
+        if self.keyframes.is_empty() {
+            return 0.0;
+        }
+        let time = self.wrap(input);
+        let next = self.keyframes.partition_point(|k| k.time <= time);
+        if next == 0 {
+            return self.keyframes[0].value;
+        }
+        if next == self.keyframes.len() {
+            return self.keyframes[next - 1].value;
+        }
+
+        let (a, b) = (&self.keyframes[next - 1], &self.keyframes[next]);
+        let duration = b.time - a.time;
+        let progress = (time - a.time) / duration;
+        let lerp = |p: f32| a.value + (b.value - a.value) * p;
+        match a.interpolation {
+            Interpolation::Linear => lerp(progress),
+            Interpolation::Step => a.value,
+            Interpolation::Ease { curve, mode } => lerp(ease(curve, mode, progress)),
+            Interpolation::Cubic => {
+                let p = progress;
+                let (p2, p3) = (p * p, p * p * p);
+                (2.0 * p3 - 3.0 * p2 + 1.0) * a.value
+                    + (p3 - 2.0 * p2 + p) * duration * self.tangent(next - 1)
+                    + (-2.0 * p3 + 3.0 * p2) * b.value
+                    + (p3 - p2) * duration * self.tangent(next)
+            }
+        }
+    }
+}
+
+impl Sampler1D for Keyframes {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let input = self.input.sample(data);
+        self.value_at(input)
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("input", &self.input)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.input]
+    }
+}
+
+impl From<Keyframes> for Movesampler1D {
+    fn from(k: Keyframes) -> Self {
+        Movesampler1D::Keyframes(k)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum Wave {
+    Sine,
+    /// 1 for the first `duty` of each cycle, then -1.
+    Square,
+    Triangle,
+    Sawtooth,
+    /// Like `Square`, but between 0 and 1, for blinking things on and off.
+    Pulse,
+}
+
+impl Wave {
+    /// The wave at `cycle` (wrapped to 0 to 1). Sine, Triangle and Sawtooth start at 0 and rise.
+    pub fn at(self, cycle: f32, duty: f32) -> f32 {
+        let cycle = cycle.rem_euclid(1.0);
+        match self {
+            Wave::Sine => (cycle * std::f32::consts::TAU).sin(),
+            Wave::Square => {
+                if cycle < duty {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            Wave::Triangle => 1.0 - 4.0 * ((cycle + 0.25).rem_euclid(1.0) - 0.5).abs(),
+            Wave::Sawtooth => 2.0 * (cycle + 0.5).rem_euclid(1.0) - 1.0,
+            Wave::Pulse => {
+                if cycle < duty {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+fn constant_one() -> Box<Movesampler1D> {
+    Box::new(Movesampler1D::Constant(1.0))
+}
+
+fn constant_zero() -> Box<Movesampler1D> {
+    Box::new(Movesampler1D::Constant(0.0))
+}
+
+fn half() -> f32 {
+    0.5
+}
+
+/// A repeating wave, `amplitude` high, running `frequency` cycles per unit of `input` (seconds
+/// unless given). `phase` shifts it by a fraction of a cycle, and `duty` is the part of each
+/// cycle that Square and Pulse spend high.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Oscillator {
+    pub wave: Wave,
+    pub frequency: Box<Movesampler1D>,
+    #[serde(default = "constant_one")]
+    pub amplitude: Box<Movesampler1D>,
+    #[serde(default = "constant_zero")]
+    pub phase: Box<Movesampler1D>,
+    #[serde(default = "half")]
+    pub duty: f32,
+    #[serde(default = "default_input")]
+    pub input: Box<Movesampler1D>,
+    /// Cycles so far. Accumulated, so a changing frequency speeds the wave up instead of jumping.
+    #[serde(skip)]
+    cycles: f32,
+    #[serde(skip)]
+    last_input: Option<f32>,
+}
+
+impl Sampler1D for Oscillator {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let input = self.input.sample(data);
+        let frequency = self.frequency.sample(data);
+        let step = input - self.last_input.unwrap_or(0.0);
+        self.last_input = Some(input);
+        // Only the fractional part matters, and keeping it small keeps it precise.
+        self.cycles = (self.cycles + frequency * step).rem_euclid(1.0);
+
+        let phase = self.phase.sample(data);
+        self.amplitude.sample(data) * self.wave.at(self.cycles + phase, self.duty)
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![
+            child("input", &self.input),
+            child("frequency", &self.frequency),
+            child("phase", &self.phase),
+            child("amplitude", &self.amplitude),
+        ]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![
+            &mut self.input,
+            &mut self.frequency,
+            &mut self.phase,
+            &mut self.amplitude,
+        ]
+    }
+}
+
+impl From<Oscillator> for Movesampler1D {
+    fn from(o: Oscillator) -> Self {
+        Movesampler1D::Oscillator(o)
+    }
+}
+
+fn default_stiffness() -> f32 {
+    200.0
+}
+
+fn default_damping() -> f32 {
+    20.0
+}
+
+/// A weight on a spring, pulled towards `target`. With `damping` below `2 * sqrt(stiffness *
+/// mass)` it overshoots and wobbles before settling, above it it creeps in without overshooting.
+/// Starts at rest on the target, and moves in seconds, so it looks the same at any frame rate.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Spring {
+    pub target: Box<Movesampler1D>,
+    #[serde(default = "default_stiffness")]
+    pub stiffness: f32,
+    #[serde(default = "default_damping")]
+    pub damping: f32,
+    #[serde(default = "one")]
+    pub mass: f32,
+    #[serde(skip)]
+    position: Option<f32>,
+    #[serde(skip)]
+    velocity: f32,
+}
+
+/// Stiff springs blow up if stepped too coarsely, so long frames are split into steps this long.
+const SPRING_STEP: f32 = 1.0 / 240.0;
+/// Frames longer than this, such as after the overlay was stalled, are treated as this long.
+const MAX_FRAME_TIME: f32 = 0.25;
+
+impl Sampler1D for Spring {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let target = self.target.sample(data);
+        let Some(mut position) = self.position else {
+            self.position = Some(target);
+            return target;
+        };
+
+        let mut remaining = data.input.delta_time.clamp(0.0, MAX_FRAME_TIME);
+        while remaining > 0.0 {
+            let dt = remaining.min(SPRING_STEP);
+            let force = self.stiffness * (target - position) - self.damping * self.velocity;
+            self.velocity += force / self.mass * dt;
+            position += self.velocity * dt;
+            remaining -= dt;
+        }
+        self.position = Some(position);
+        position
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("target", &self.target)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.target]
+    }
+}
+
+impl From<Spring> for Movesampler1D {
+    fn from(s: Spring) -> Self {
+        Movesampler1D::Spring(s)
+    }
+}
+
+fn default_half_life() -> f32 {
+    0.1
+}
+
+/// Follows `target`, closing half the distance every `half_life` seconds. Starts on the target.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Smooth {
+    pub target: Box<Movesampler1D>,
+    #[serde(default = "default_half_life")]
+    pub half_life: f32,
+    #[serde(skip)]
+    value: Option<f32>,
+}
+
+impl Sampler1D for Smooth {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let target = self.target.sample(data);
+        let value = match self.value {
+            Some(value) if self.half_life > 0.0 => {
+                let remaining = 0.5f32.powf(data.input.delta_time.max(0.0) / self.half_life);
+                target + (value - target) * remaining
+            }
+            _ => target,
+        };
+        self.value = Some(value);
+        value
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("target", &self.target)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.target]
+    }
+}
+
+impl From<Smooth> for Movesampler1D {
+    fn from(s: Smooth) -> Self {
+        Movesampler1D::Smooth(s)
+    }
+}
+
+/// How a triggered value rises to 1 and falls back to 0, in seconds.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+pub struct Envelope {
+    /// Time to rise to 1.
+    #[serde(default)]
+    pub attack: f32,
+    /// Time to stay at 1 once there. The value also stays there while the trigger is held.
+    #[serde(default)]
+    pub hold: f32,
+    /// Time to fall from 1 back to 0.
+    pub release: f32,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    #[default]
+    Idle,
+    Attack,
+    Hold,
+    Release,
+}
+
+/// Where an `Envelope` is up to. Kept apart from the `Envelope` so samplers can serialize one
+/// without the other.
+#[derive(Debug, Default)]
+pub struct EnvelopeState {
+    stage: Stage,
+    level: f32,
+    held_for: f32,
+    gate: bool,
+}
+
+impl EnvelopeState {
+    /// Moves the envelope on by `delta_time` seconds and returns its level. It starts when `gate`
+    /// turns true, and with `retrigger` starts over, from wherever it is, on every later press
+    /// instead of only once it is back at 0.
+    pub fn step(
+        &mut self,
+        envelope: &Envelope,
+        gate: bool,
+        retrigger: bool,
+        delta_time: f32,
+    ) -> f32 {
+        let pressed = gate && !self.gate;
+        self.gate = gate;
+        self.advance(envelope, delta_time.max(0.0));
+        if pressed && (retrigger || self.stage == Stage::Idle) {
+            self.stage = Stage::Attack;
+            // Finishes a zero length attack straight away.
+            self.advance(envelope, 0.0);
+        }
+        self.level
+    }
+
+    fn advance(&mut self, envelope: &Envelope, mut remaining: f32) {
+        loop {
+            match self.stage {
+                Stage::Idle => return,
+                Stage::Attack => {
+                    let needed = (1.0 - self.level) * envelope.attack;
+                    if envelope.attack > 0.0 && remaining < needed {
+                        self.level += remaining / envelope.attack;
+                        return;
+                    }
+                    remaining -= needed.max(0.0);
+                    self.level = 1.0;
+                    self.held_for = 0.0;
+                    self.stage = Stage::Hold;
+                }
+                Stage::Hold => {
+                    let needed = envelope.hold - self.held_for;
+                    if self.gate || remaining < needed {
+                        self.held_for += remaining;
+                        return;
+                    }
+                    remaining -= needed.max(0.0);
+                    self.stage = Stage::Release;
+                }
+                Stage::Release => {
+                    let needed = self.level * envelope.release;
+                    if envelope.release > 0.0 && remaining < needed {
+                        self.level -= remaining / envelope.release;
+                        return;
+                    }
+                    self.level = 0.0;
+                    self.stage = Stage::Idle;
+                }
+            }
+        }
+    }
+}
+
+fn default_retrigger() -> bool {
+    true
+}
+
+/// Rises to 1 and falls back to 0 along an envelope each time `trigger` turns true. Like
+/// `Spring`, it runs in seconds, so it looks the same at any frame rate.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Decay {
+    pub trigger: Box<Movesampler1D>,
+    #[serde(flatten)]
+    pub envelope: Envelope,
+    /// Whether a new trigger restarts the envelope before it has finished.
+    #[serde(default = "default_retrigger")]
+    pub retrigger: bool,
+    #[serde(skip)]
+    state: EnvelopeState,
+}
+
+impl Sampler1D for Decay {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let gate = is_true(self.trigger.sample(data));
+        self.state
+            .step(&self.envelope, gate, self.retrigger, data.input.delta_time)
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("trigger", &self.trigger)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.trigger]
+    }
+}
+
+impl From<Decay> for Movesampler1D {
+    fn from(d: Decay) -> Self {
+        Movesampler1D::Decay(d)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ease_between_ranges() {
+        let mut sampler: Movesampler1D = serde_jsonrc::from_str(
+            r#"{
+  "type": "Ease",
+  "value": { "speed": 1.0 },
+  "curve": "Cubic",
+  "mode": "In",
+  "before_min": 1.0,
+  "before_max": 3.0,
+  "after_min": 40.0,
+  "after_max": 20.0
+}"#,
+        )
+        .unwrap();
+        let mut at = |t: f32| {
+            let mut data = SamplerData::new();
+            data.t = t;
+            sampler.sample(&mut data)
+        };
+
+        assert_eq!(at(0.0), 40.0);
+        assert_eq!(at(1.0), 40.0);
+        assert_eq!(at(2.0), 37.5);
+        assert_eq!(at(3.0), 20.0);
+        assert_eq!(at(10.0), 20.0);
+
+        for curve in [
+            EaseCurve::Sine,
+            EaseCurve::Circ,
+            EaseCurve::Quad,
+            EaseCurve::Expo,
+            EaseCurve::Back,
+            EaseCurve::Bounce,
+            EaseCurve::Elastic,
+        ] {
+            for mode in [EaseMode::In, EaseMode::Out, EaseMode::InOut] {
+                assert!(
+                    ease(curve, mode, 0.0).abs() < 1e-5,
+                    "{:?} {:?}",
+                    curve,
+                    mode
+                );
+                assert!(
+                    (ease(curve, mode, 1.0) - 1.0).abs() < 1e-5,
+                    "{:?} {:?}",
+                    curve,
+                    mode
+                );
+                assert!(
+                    (ease(curve, mode, 0.5) - ease(curve, mode, 0.5 - 1e-3)).abs() < 0.05,
+                    "{:?} {:?} is not continuous",
+                    curve,
+                    mode
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn in_out_curves_meet_at_the_midpoint() {
+        for curve in [
+            EaseCurve::Sine,
+            EaseCurve::Circ,
+            EaseCurve::Quad,
+            EaseCurve::Expo,
+            EaseCurve::Back,
+            EaseCurve::Bounce,
+            EaseCurve::Elastic,
+        ] {
+            for (progress, expected) in [(0.0, 0.0), (0.5, 0.5), (1.0, 1.0)] {
+                let value = ease(curve, EaseMode::InOut, progress);
+                assert!(
+                    (value - expected).abs() < 1e-3,
+                    "{:?} is {} at {}",
+                    curve,
+                    value,
+                    progress
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn keyframes() {
+        let mut sampler: Movesampler1D = serde_jsonrc::from_str(
+            r#"{
+  "type": "Keyframes",
+  "keyframes": [
+    { "time": 0.0, "value": 0.0 },
+    { "time": 1.0, "value": 10.0, "interpolation": "Step" },
+    { "time": 2.0, "value": 20.0, "interpolation": "Cubic" },
+    { "time": 3.0, "value": 30.0, "interpolation": { "Ease": { "curve": "Quad", "mode": "In" } } },
+    { "time": 4.0, "value": 40.0 }
+  ]
+}"#,
+        )
+        .unwrap();
+        let Movesampler1D::Keyframes(keyframes) = &mut sampler else {
+            unreachable!()
+        };
+
+        let values: Vec<f32> = [-1.0, 0.5, 1.5, 2.5, 3.5, 4.0, 5.0]
+            .into_iter()
+            .map(|t| keyframes.value_at(t))
+            .collect();
+        // The cubic segment lies on a straight line, so it matches linear interpolation.
+        assert_eq!(values, [0.0, 5.0, 10.0, 25.0, 32.5, 40.0, 40.0]);
+
+        keyframes.repeat = Repeat::Loop;
+        assert_eq!(keyframes.value_at(4.5), 5.0);
+        assert_eq!(keyframes.value_at(-3.5), 5.0);
+        keyframes.repeat = Repeat::PingPong;
+        assert_eq!(keyframes.value_at(7.5), 5.0);
+
+        // With no input given, keyframes play against the time.
+        let mut data = SamplerData::new();
+        data.t = 0.5;
+        assert_eq!(sampler.sample(&mut data), 5.0);
+    }
+
+    #[test]
+    fn oscillators() {
+        let at = |wave: Wave| [0.0, 0.25, 0.5, 0.75].map(|c| (wave.at(c, 0.25) * 100.0).round());
+        assert_eq!(at(Wave::Sine), [0.0, 100.0, 0.0, -100.0]);
+        assert_eq!(at(Wave::Triangle), [0.0, 100.0, 0.0, -100.0]);
+        assert_eq!(at(Wave::Sawtooth), [0.0, 50.0, -100.0, -50.0]);
+        assert_eq!(at(Wave::Square), [100.0, -100.0, -100.0, -100.0]);
+        assert_eq!(at(Wave::Pulse), [100.0, 0.0, 0.0, 0.0]);
+
+        // Doubling the frequency halfway through carries on from where the wave was.
+        let mut sampler: Movesampler1D = serde_jsonrc::from_str(
+            r#"{
+  "type": "Oscillator",
+  "wave": "Sawtooth",
+  "frequency": { "type": "If", "condition": { "keys": ["LShift"] }, "then": 2.0, "else": 1.0 },
+  "amplitude": 10.0,
+  "phase": 0.5
+}"#,
+        )
+        .unwrap();
+        let mut data = SamplerData::new();
+        let mut values = Vec::new();
+        for frame in 1..=4 {
+            data.t = frame as f32 * 0.125;
+            if frame == 3 {
+                data.input.keys = vec![device_query::Keycode::LShift];
+            }
+            values.push(sampler.sample(&mut data));
+        }
+        assert_eq!(values, [-7.5, -5.0, 0.0, 5.0]);
+    }
+
+    /// Runs `sampler` for `seconds` at `fps`, with the target jumping from 0 to 1 on the first
+    /// frame, and returns where it ended up.
+    fn follow(json: &str, fps: f32, seconds: f32) -> f32 {
+        let mut sampler: Movesampler1D = serde_jsonrc::from_str(json).unwrap();
+        let mut data = SamplerData::new();
+        sampler.sample(&mut data);
+        data.input.keys = vec![device_query::Keycode::Space];
+        data.input.delta_time = 1.0 / fps;
+        let mut value = 0.0;
+        for _ in 0..(seconds * fps).round() as usize {
+            value = sampler.sample(&mut data);
+        }
+        value
+    }
+
+    #[test]
+    fn springs_and_smoothing_ignore_frame_rate() {
+        let smooth = r#"{ "type": "Smooth", "target": { "keys": ["Space"] }, "half_life": 0.2 }"#;
+        for fps in [30.0, 60.0, 120.0] {
+            assert!((follow(smooth, fps, 0.2) - 0.5).abs() < 1e-4, "{}", fps);
+            assert!((follow(smooth, fps, 0.4) - 0.75).abs() < 1e-4, "{}", fps);
+        }
+
+        let spring = r#"{ "type": "Spring", "target": { "keys": ["Space"] } }"#;
+        let at_60 = follow(spring, 60.0, 0.3);
+        // The default spring is underdamped, so it overshoots before it settles.
+        assert!(at_60 > 1.0, "{}", at_60);
+        for fps in [30.0, 120.0] {
+            assert!((follow(spring, fps, 0.3) - at_60).abs() < 0.01, "{}", fps);
+        }
+        assert!((follow(spring, 60.0, 2.0) - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn envelopes() {
+        let mut sampler: Movesampler1D = serde_jsonrc::from_str(
+            r#"{
+  "type": "Decay",
+  "trigger": { "keys": ["Space"] },
+  "attack": 0.1,
+  "hold": 0.1,
+  "release": 0.2,
+  "retrigger": false
+}"#,
+        )
+        .unwrap();
+        let mut data = SamplerData::new();
+        data.input.delta_time = 0.05;
+        let mut values = Vec::new();
+        for frame in 0..12 {
+            // Pressed for a frame, then again mid release, which is ignored without retrigger.
+            data.input.keys = if frame == 0 || frame == 6 {
+                vec![device_query::Keycode::Space]
+            } else {
+                Vec::new()
+            };
+            values.push((sampler.sample(&mut data) * 100.0).round());
+        }
+        assert_eq!(
+            values,
+            [0.0, 50.0, 100.0, 100.0, 100.0, 75.0, 50.0, 25.0, 0.0, 0.0, 0.0, 0.0]
+        );
+
+        // The time based MouseClick stays up while held, and restarts on each press.
+        let mut click: Movesampler1D = serde_jsonrc::from_str(
+            r#"{
+  "mouse_button": 1,
+  "force_full_cycle": false,
+  "envelope": { "release": 0.5 }
+}"#,
+        )
+        .unwrap();
+        let mut values = Vec::new();
+        for (buttons, delta_time) in [
+            (true, 0.1),
+            (true, 1.0),
+            (false, 0.25),
+            (true, 0.1),
+            (false, 0.1),
+            (false, 1.0),
+        ] {
+            data.input.buttons = vec![false, buttons];
+            data.input.delta_time = delta_time;
+            values.push(click.sample(&mut data));
+        }
+        assert_eq!(values, [1.0, 1.0, 0.5, 1.0, 0.8, 0.0]);
+    }
+}
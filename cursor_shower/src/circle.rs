@@ -3,7 +3,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    gen_shape::{Drawable, ShapeRaw},
+    gen_shape::{DrawCommand, Drawable, ResolvedShape, ShapeRaw},
     linear_samplers::SamplerData,
 };
 
@@ -15,14 +15,15 @@ pub struct Circle {
 }
 
 impl Drawable for Circle {
-    fn draw(&mut self, d: &mut RaylibDrawHandle, _t: &mut SamplerData, pos: (i32, i32)) {
-        d.draw_circle_gradient(
-            (pos.0) as i32,
-            (pos.1) as i32,
-            self.radius,
-            self.inner_color,
-            self.outer_color,
-        );
+    fn evaluate(&mut self, _t: &mut SamplerData, pos: (i32, i32)) -> DrawCommand {
+        DrawCommand {
+            position: pos,
+            shape: ResolvedShape::Circle {
+                radius: self.radius,
+                inner_color: self.inner_color,
+                outer_color: self.outer_color,
+            },
+        }
     }
 }
 
@@ -0,0 +1,589 @@
+//! Explains why a cursor file failed to deserialize. serde only reports "data did not match any
+//! variant" for the untagged samplers, so the parsed value is walked against the cursor's JSON
+//! schema instead, which knows every variant and field.
+
+use schemars::schema::{InstanceType, RootSchema, Schema, SchemaObject, SingleOrVec};
+use serde_jsonrc::Value;
+
+use crate::gen_shape::Shape;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    Index(usize),
+    Key(String),
+}
+
+/// A location inside a JSON document, displayed like `[3].movement[0].radius`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JsonPath(pub Vec<PathSegment>);
+
+impl std::fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "(root)");
+        }
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+                PathSegment::Key(key) if i == 0 => write!(f, "{}", key)?,
+                PathSegment::Key(key) => write!(f, ".{}", key)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub path: JsonPath,
+    pub message: String,
+}
+
+/// Finds the first place where `value` is not a valid cursor, if there is one.
+pub fn diagnose(value: &Value) -> Option<Diagnostic> {
+    let root = schemars::schema_for!(Vec<Shape>);
+    let walker = Walker { root: &root };
+    let mut path = Vec::new();
+    walker
+        .check(value, &Schema::Object(root.schema.clone()), &mut path)
+        .err()
+}
+
+struct Walker<'a> {
+    root: &'a RootSchema,
+}
+
+fn kind_of(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+fn matches_type(value: &Value, instance_type: &InstanceType) -> bool {
+    match instance_type {
+        InstanceType::Null => value.is_null(),
+        InstanceType::Boolean => value.is_boolean(),
+        InstanceType::Object => value.is_object(),
+        InstanceType::Array => value.is_array(),
+        InstanceType::Number => value.is_number(),
+        InstanceType::String => value.is_string(),
+        InstanceType::Integer => value.is_u64() || value.is_i64(),
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// The name `given` was most likely meant to be: one it abbreviates, or failing that one a typo
+/// or two away, ignoring case.
+fn closest_name<'a>(given: &str, names: &'a [String]) -> Option<&'a String> {
+    let given = given.to_lowercase();
+    let typos = (given.len() / 3).clamp(1, 2);
+    names
+        .iter()
+        .map(|name| {
+            let lower = name.to_lowercase();
+            (
+                name,
+                !lower.starts_with(&given),
+                edit_distance(&given, &lower),
+            )
+        })
+        .filter(|(_, not_prefix, distance)| !not_prefix || *distance <= typos)
+        .min_by_key(|(_, not_prefix, distance)| (*not_prefix, *distance))
+        .map(|(name, _, _)| name)
+}
+
+fn format_names<'a>(names: impl IntoIterator<Item = &'a String>) -> String {
+    names
+        .into_iter()
+        .map(|n| format!("`{}`", n))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl<'a> Walker<'a> {
+    fn resolve(&self, schema: &'a Schema) -> Option<(&'a SchemaObject, Option<&'a str>)> {
+        match schema {
+            Schema::Bool(_) => None,
+            Schema::Object(object) => match &object.reference {
+                Some(reference) => {
+                    let name = reference.trim_start_matches("#/definitions/");
+                    match self.root.definitions.get(name) {
+                        Some(Schema::Object(definition)) => Some((definition, Some(name))),
+                        _ => None,
+                    }
+                }
+                None => Some((object, None)),
+            },
+        }
+    }
+
+    /// Every alternative of a schema, looking through references to nested alternatives.
+    fn variants(&self, schema: &'a Schema) -> Vec<(Option<&'a str>, &'a Schema)> {
+        let Some((object, name)) = self.resolve(schema) else {
+            return Vec::new();
+        };
+        let alternatives = object
+            .subschemas
+            .as_ref()
+            .and_then(|s| s.any_of.as_ref().or(s.one_of.as_ref()));
+        match alternatives {
+            Some(alternatives) if object.instance_type.is_none() && object.object.is_none() => {
+                alternatives.iter().flat_map(|a| self.variants(a)).collect()
+            }
+            _ => vec![(name, schema)],
+        }
+    }
+
+    fn tag(&self, schema: &'a Schema) -> Option<&'a str> {
+        let (object, _) = self.resolve(schema)?;
+        let tag = object.object.as_ref()?.properties.get("type")?;
+        match tag {
+            Schema::Object(tag) => tag.enum_values.as_ref()?.first()?.as_str(),
+            Schema::Bool(_) => None,
+        }
+    }
+
+    fn properties(&self, schema: &'a Schema) -> Option<&'a schemars::schema::ObjectValidation> {
+        let (object, _) = self.resolve(schema)?;
+        object.object.as_deref()
+    }
+
+    /// How well an object fits a variant: fields it knows about count for it, missing required
+    /// fields count against it, unless the object has a misspelling of them.
+    fn closeness(&self, value: &Value, schema: &'a Schema) -> (i64, Vec<String>, Vec<String>) {
+        let (Some(fields), Some(value)) = (self.properties(schema), value.as_object()) else {
+            return (i64::MIN, Vec::new(), Vec::new());
+        };
+        let missing: Vec<String> = fields
+            .required
+            .iter()
+            .filter(|r| !value.contains_key(*r))
+            .cloned()
+            .collect();
+        let unknown: Vec<String> = value
+            .keys()
+            .filter(|k| !fields.properties.contains_key(*k))
+            .cloned()
+            .collect();
+        let known = (value.len() - unknown.len()) as i64;
+        let misspelled = missing
+            .iter()
+            .filter(|m| unknown.iter().any(|u| edit_distance(m, u) <= 2))
+            .count() as i64;
+        (
+            2 * known + misspelled - 2 * missing.len() as i64,
+            missing,
+            unknown,
+        )
+    }
+
+    fn fail(path: &[PathSegment], message: String) -> Result<(), Diagnostic> {
+        Err(Diagnostic {
+            path: JsonPath(path.to_vec()),
+            message,
+        })
+    }
+
+    fn check(
+        &self,
+        value: &Value,
+        schema: &'a Schema,
+        path: &mut Vec<PathSegment>,
+    ) -> Result<(), Diagnostic> {
+        let variants = self.variants(schema);
+        if variants.len() > 1 {
+            return self.check_variants(value, schema, variants, path);
+        }
+        let Some((object, _)) = self.resolve(schema) else {
+            return Ok(());
+        };
+
+        if let Some(all_of) = object.subschemas.as_ref().and_then(|s| s.all_of.as_ref()) {
+            for part in all_of {
+                self.check(value, part, path)?;
+            }
+        }
+
+        if let Some(instance_type) = &object.instance_type {
+            let types: &[InstanceType] = match instance_type {
+                SingleOrVec::Single(t) => std::slice::from_ref(t),
+                SingleOrVec::Vec(ts) => ts,
+            };
+            if !types.iter().any(|t| matches_type(value, t)) {
+                let expected: Vec<String> = types
+                    .iter()
+                    .map(|t| format!("{:?}", t).to_lowercase())
+                    .collect();
+                return Self::fail(
+                    path,
+                    format!(
+                        "expected {}, found {}",
+                        expected.join(" or "),
+                        kind_of(value)
+                    ),
+                );
+            }
+        }
+
+        if let Some(values) = &object.enum_values {
+            if !values
+                .iter()
+                .any(|v| v.as_str().is_some() && v.as_str() == value.as_str())
+            {
+                let names: Vec<String> = values
+                    .iter()
+                    .filter_map(|v| v.as_str())
+                    .map(String::from)
+                    .collect();
+                if let Some(given) = value.as_str() {
+                    if let Some(name) = closest_name(given, &names) {
+                        return Self::fail(
+                            path,
+                            format!("unknown value `{}`, did you mean `{}`?", given, name),
+                        );
+                    }
+                }
+                return Self::fail(path, format!("expected one of {}", format_names(&names)));
+            }
+        }
+
+        if let (Some(number), Some(format)) = (value.as_f64(), object.format.as_deref()) {
+            let range = match format {
+                "uint8" => Some((0.0, u8::MAX as f64)),
+                "uint16" => Some((0.0, u16::MAX as f64)),
+                "uint32" => Some((0.0, u32::MAX as f64)),
+                "uint" | "uint64" => Some((0.0, f64::MAX)),
+                _ => None,
+            };
+            if let Some((min, max)) = range {
+                if number < min || number > max {
+                    return Self::fail(path, format!("{} is out of range for {}", number, format));
+                }
+            }
+        }
+
+        if let (Some(fields), Some(map)) = (&object.object, value.as_object()) {
+            let missing: Vec<String> = fields
+                .required
+                .iter()
+                .filter(|r| !map.contains_key(*r))
+                .cloned()
+                .collect();
+            if !missing.is_empty() {
+                return Self::fail(path, format!("missing fields {}", format_names(&missing)));
+            }
+            for (key, field_schema) in &fields.properties {
+                if let Some(field) = map.get(key) {
+                    path.push(PathSegment::Key(key.clone()));
+                    self.check(field, field_schema, path)?;
+                    path.pop();
+                }
+            }
+        }
+
+        if let (Some(items), Some(array)) = (&object.array, value.as_array()) {
+            if let Some(SingleOrVec::Single(item_schema)) = &items.items {
+                for (i, item) in array.iter().enumerate() {
+                    path.push(PathSegment::Index(i));
+                    self.check(item, item_schema, path)?;
+                    path.pop();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_variants(
+        &self,
+        value: &Value,
+        schema: &'a Schema,
+        variants: Vec<(Option<&'a str>, &'a Schema)>,
+        path: &mut Vec<PathSegment>,
+    ) -> Result<(), Diagnostic> {
+        let name = self
+            .resolve(schema)
+            .and_then(|(_, name)| name)
+            .unwrap_or("value");
+
+        // A `type` field picks the variant outright.
+        if let Some(tag) = value.get("type").and_then(Value::as_str) {
+            if let Some((_, variant)) = variants.iter().find(|(_, v)| self.tag(v) == Some(tag)) {
+                return self.check(value, variant, path);
+            }
+            let tags: Vec<String> = variants
+                .iter()
+                .filter_map(|(_, v)| self.tag(v))
+                .map(String::from)
+                .collect();
+            if !tags.is_empty() {
+                return Self::fail(
+                    path,
+                    format!(
+                        "unknown {} type `{}`, expected one of {}",
+                        name,
+                        tag,
+                        format_names(&tags)
+                    ),
+                );
+            }
+        }
+
+        let mut failures = Vec::new();
+        for (variant_name, variant) in &variants {
+            if self.tag(variant).is_some() {
+                continue;
+            }
+            match self.check(value, variant, &mut path.clone()) {
+                Ok(()) => return Ok(()),
+                Err(diagnostic) => failures.push((*variant_name, *variant, diagnostic)),
+            }
+        }
+
+        // A variant whose own fields all lined up got further than the others, so its nested
+        // problem is the one worth reporting.
+        let closest = failures
+            .iter()
+            .max_by_key(|(_, variant, diagnostic)| {
+                (diagnostic.path.0.len(), self.closeness(value, variant).0)
+            })
+            .filter(|(_, variant, _)| self.closeness(value, variant).0 > i64::MIN);
+        match closest {
+            Some((_, _, diagnostic)) if diagnostic.path.0.len() > path.len() => {
+                Err(diagnostic.clone())
+            }
+            Some((variant_name, variant, _)) => {
+                let (_, missing, unknown) = self.closeness(value, variant);
+                let mut message = format!(
+                    "does not match any {} variant; closest is `{}`",
+                    name,
+                    variant_name.unwrap_or("?")
+                );
+                if !missing.is_empty() {
+                    message += &format!(", missing fields {}", format_names(&missing));
+                }
+                if !unknown.is_empty() {
+                    message += &format!(", unknown fields {}", format_names(&unknown));
+                }
+                Self::fail(path, message)
+            }
+            None => Self::fail(path, format!("{} is not a valid {}", kind_of(value), name)),
+        }
+    }
+}
+
+/// Finds the 1-based line and column where the value at `path` starts in a JSON-with-comments
+/// document.
+pub fn locate(text: &str, path: &JsonPath) -> Option<(usize, usize)> {
+    let mut scanner = Scanner {
+        bytes: text.as_bytes(),
+        pos: 0,
+    };
+    scanner.skip_whitespace();
+    for segment in &path.0 {
+        match segment {
+            PathSegment::Key(key) => {
+                scanner.expect(b'{')?;
+                loop {
+                    scanner.skip_whitespace();
+                    let found = scanner.string()?;
+                    scanner.skip_whitespace();
+                    scanner.expect(b':')?;
+                    scanner.skip_whitespace();
+                    if found == key.as_bytes() {
+                        break;
+                    }
+                    scanner.skip_value()?;
+                    scanner.skip_whitespace();
+                    scanner.expect(b',')?;
+                }
+            }
+            PathSegment::Index(index) => {
+                scanner.expect(b'[')?;
+                for _ in 0..*index {
+                    scanner.skip_whitespace();
+                    scanner.skip_value()?;
+                    scanner.skip_whitespace();
+                    scanner.expect(b',')?;
+                }
+                scanner.skip_whitespace();
+            }
+        }
+    }
+
+    let before = &text[..scanner.pos];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    Some((line, column))
+}
+
+struct Scanner<'t> {
+    bytes: &'t [u8],
+    pos: usize,
+}
+
+impl<'t> Scanner<'t> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        (self.peek()? == byte).then(|| self.pos += 1)
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            match (self.peek(), self.bytes.get(self.pos + 1)) {
+                (Some(b' ' | b'\t' | b'\n' | b'\r'), _) => self.pos += 1,
+                (Some(b'/'), Some(b'/')) => {
+                    while self.peek().is_some_and(|b| b != b'\n') {
+                        self.pos += 1;
+                    }
+                }
+                (Some(b'/'), Some(b'*')) => {
+                    self.pos += 2;
+                    while self.pos < self.bytes.len() && !self.bytes[self.pos..].starts_with(b"*/")
+                    {
+                        self.pos += 1;
+                    }
+                    self.pos += 2;
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn string(&mut self) -> Option<&'t [u8]> {
+        self.expect(b'"')?;
+        let start = self.pos;
+        loop {
+            match self.peek()? {
+                b'\\' => self.pos += 2,
+                b'"' => break,
+                _ => self.pos += 1,
+            }
+        }
+        self.pos += 1;
+        Some(&self.bytes[start..self.pos - 1])
+    }
+
+    fn skip_value(&mut self) -> Option<()> {
+        match self.peek()? {
+            b'"' => {
+                self.string()?;
+            }
+            b'{' | b'[' => {
+                let mut depth = 0;
+                loop {
+                    self.skip_whitespace();
+                    match self.peek()? {
+                        b'"' => {
+                            self.string()?;
+                            continue;
+                        }
+                        b'{' | b'[' => depth += 1,
+                        b'}' | b']' => depth -= 1,
+                        _ => {}
+                    }
+                    self.pos += 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+            }
+            _ => {
+                while !matches!(
+                    self.peek(),
+                    None | Some(b',' | b'}' | b']' | b' ' | b'\t' | b'\n' | b'\r' | b'/')
+                ) {
+                    self.pos += 1;
+                }
+            }
+        }
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURSOR: &str = r#"[
+  // The first shape is fine.
+  { "enabled": 1.0, "movement": [], "shape": { "type": "NoDraw" } },
+  {
+    "enabled": { "speed": 1.0 },
+    "movement": [
+      {
+        "type": "Orbit",
+        "speed": 6.0,
+        "eccentricity": 1.0,
+        "angle_top": 0.0,
+        "angle_bottom": 3.0,
+        "radius": {
+          "mouse_timer_decrease": 0.1,
+          "mouse_buton": 1,
+          "force_full_cycle": true
+        }
+      }
+    ],
+    "shape": { "type": "NoDraw" }
+  }
+]"#;
+
+    #[test]
+    fn closest_untagged_variant() {
+        let value: Value = serde_jsonrc::from_str(CURSOR).unwrap();
+        let diagnostic = diagnose(&value).unwrap();
+
+        assert_eq!(diagnostic.path.to_string(), "[1].movement[0].radius");
+        assert_eq!(
+            diagnostic.message,
+            "does not match any Movesampler1D variant; closest is `MouseClick`, \
+             missing fields `mouse_button`, unknown fields `mouse_buton`"
+        );
+        assert_eq!(locate(CURSOR, &diagnostic.path), Some((13, 19)));
+    }
+
+    #[test]
+    fn example_cursors_are_valid() {
+        for name in ["myCursor", "spinningDots"] {
+            let text = std::fs::read_to_string(format!("../cursors/{}.jsonc", name)).unwrap();
+            let value: Value = serde_jsonrc::from_str(&text).unwrap();
+            assert_eq!(diagnose(&value), None, "{}", name);
+        }
+    }
+
+    #[test]
+    fn unknown_tag() {
+        let value: Value = serde_jsonrc::from_str(
+            r#"[{ "enabled": 1.0, "movement": [{ "type": "Orbt" }], "shape": { "type": "NoDraw" } }]"#,
+        )
+        .unwrap();
+        let diagnostic = diagnose(&value).unwrap();
+
+        assert_eq!(diagnostic.path.to_string(), "[0].movement[0]");
+        assert_eq!(
+            diagnostic.message,
+            "unknown Movesampler2D type `Orbt`, expected one of `Orbit`, `Offset`, `Mouse`"
+        );
+    }
+}
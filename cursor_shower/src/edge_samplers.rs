@@ -0,0 +1,153 @@
+//! Samplers that react to a value turning true or false, rather than to it being true. They keep
+//! what `value` was on the previous frame, so each one should be sampled once per frame.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    linear_samplers::{child, Child, Movesampler1D, Sampler1D, SamplerData},
+    logic_samplers::{from_bool, is_true},
+};
+
+/// 1.0 on the frame `value` turns true, 0.0 otherwise. A value that starts out true counts as
+/// turning true on the first frame.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct OnPress {
+    pub value: Box<Movesampler1D>,
+    #[serde(skip)]
+    was_true: bool,
+}
+
+impl Sampler1D for OnPress {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let now = is_true(self.value.sample(data));
+        let pressed = now && !self.was_true;
+        self.was_true = now;
+        from_bool(pressed)
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("value", &self.value)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.value]
+    }
+}
+
+impl From<OnPress> for Movesampler1D {
+    fn from(o: OnPress) -> Self {
+        Movesampler1D::OnPress(o)
+    }
+}
+
+/// 1.0 on the frame `value` turns false after being true, 0.0 otherwise.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct OnRelease {
+    pub value: Box<Movesampler1D>,
+    #[serde(skip)]
+    was_true: bool,
+}
+
+impl Sampler1D for OnRelease {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let now = is_true(self.value.sample(data));
+        let released = !now && self.was_true;
+        self.was_true = now;
+        from_bool(released)
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("value", &self.value)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.value]
+    }
+}
+
+impl From<OnRelease> for Movesampler1D {
+    fn from(o: OnRelease) -> Self {
+        Movesampler1D::OnRelease(o)
+    }
+}
+
+/// Seconds `value` has been true without a break, counted from the frame it turned true, or 0.0
+/// while it is false.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct HeldFor {
+    pub value: Box<Movesampler1D>,
+    #[serde(skip)]
+    held: Option<f32>,
+}
+
+impl Sampler1D for HeldFor {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        self.held = if is_true(self.value.sample(data)) {
+            Some(self.held.map_or(0.0, |held| held + data.input.delta_time))
+        } else {
+            None
+        };
+        self.held.unwrap_or(0.0)
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("value", &self.value)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.value]
+    }
+}
+
+impl From<HeldFor> for Movesampler1D {
+    fn from(h: HeldFor) -> Self {
+        Movesampler1D::HeldFor(h)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presses_releases_and_holds() {
+        let mut samplers: Vec<Movesampler1D> = serde_jsonrc::from_str(
+            r#"[
+  { "type": "OnPress", "value": { "keys": ["F"] } },
+  { "type": "OnRelease", "value": { "keys": ["F"] } },
+  { "type": "HeldFor", "value": { "keys": ["F"] } },
+  { "mouse_click_counter_button": 1 }
+]"#,
+        )
+        .unwrap();
+        let mut data = SamplerData::new();
+        data.input.delta_time = 0.25;
+        let mut frames = Vec::new();
+        for down in [true, true, true, false, false, true] {
+            data.input.keys = if down {
+                vec![device_query::Keycode::F]
+            } else {
+                Vec::new()
+            };
+            data.input.buttons = vec![false, down];
+            frames.push(
+                samplers
+                    .iter_mut()
+                    .map(|s| s.sample(&mut data))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        assert_eq!(
+            frames,
+            [
+                [1.0, 0.0, 0.0, 1.0],
+                [0.0, 0.0, 0.25, 1.0],
+                [0.0, 0.0, 0.5, 1.0],
+                [0.0, 1.0, 0.0, 1.0],
+                [0.0, 0.0, 0.0, 1.0],
+                [1.0, 0.0, 0.0, 2.0],
+            ]
+        );
+    }
+}
@@ -3,12 +3,48 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    linear_samplers::{Movesampler1D, Sampler1D, SamplerData},
+    diagnostics::PathSegment,
+    linear_samplers::{child, Child, Movesampler1D, Sampler1D, SamplerData},
     movement::{Movesampler2D, Sampler2D},
 };
 
 pub trait Drawable {
-    fn draw(&mut self, d: &mut RaylibDrawHandle, t: &mut SamplerData, coords: (i32, i32));
+    /// Samples everything the shape needs for this frame, without touching any renderer.
+    fn evaluate(&mut self, data: &mut SamplerData, coords: (i32, i32)) -> DrawCommand;
+
+    /// The samplers the shape reads from, for walking a cursor without drawing it.
+    fn children(&self) -> Vec<Child<'_>> {
+        Vec::new()
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        Vec::new()
+    }
+}
+
+/// A shape resolved for a single frame, ready to be drawn by a `RenderTarget`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrawCommand {
+    pub position: (i32, i32),
+    pub shape: ResolvedShape,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolvedShape {
+    Circle {
+        radius: f32,
+        inner_color: Color,
+        outer_color: Color,
+    },
+    NoDraw,
+    Rect {
+        width: f32,
+        height: f32,
+        color_tl: Color,
+        color_tr: Color,
+        color_bl: Color,
+        color_br: Color,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -16,16 +52,44 @@ pub struct Shape {
     pub enabled: Movesampler1D,
     pub movement: Vec<Movesampler2D>,
     pub shape: ShapeRaw,
+    /// Seeds the shape's `Random` and `Noise1D` samplers, so they play out the same way every
+    /// run. Without one they differ each run, but stay the same when the file is reloaded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
 }
 
 impl Drawable for Shape {
-    fn draw(&mut self, d: &mut RaylibDrawHandle, data: &mut SamplerData, win: (i32, i32)) {
+    fn evaluate(&mut self, data: &mut SamplerData, win: (i32, i32)) -> DrawCommand {
         let (x, y) = self.movement.iter_mut().fold((0.0, 0.0), |(x, y), m| {
             let (dx, dy) = m.sample(data);
             (x + dx, y + dy)
         });
         self.shape
-            .draw(d, data, (x as i32 + win.0, y as i32 + win.1));
+            .evaluate(data, (x as i32 + win.0, y as i32 + win.1))
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        let key = |k: &str| PathSegment::Key(k.to_string());
+        let mut children = vec![child("enabled", &self.enabled)];
+        for (i, movement) in self.movement.iter().enumerate() {
+            for (path, sampler) in movement.children() {
+                let prefix = [key("movement"), PathSegment::Index(i)];
+                children.push(([&prefix[..], &path].concat(), sampler));
+            }
+        }
+        for (path, sampler) in self.shape.children() {
+            children.push(([&[key("shape")][..], &path].concat(), sampler));
+        }
+        children
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        let mut children = vec![&mut self.enabled];
+        for movement in &mut self.movement {
+            children.extend(movement.children_mut());
+        }
+        children.extend(self.shape.children_mut());
+        children
     }
 }
 
@@ -38,11 +102,27 @@ pub enum ShapeRaw {
 }
 
 impl Drawable for ShapeRaw {
-    fn draw(&mut self, d: &mut RaylibDrawHandle, data: &mut SamplerData, pos: (i32, i32)) {
+    fn evaluate(&mut self, data: &mut SamplerData, pos: (i32, i32)) -> DrawCommand {
         match self {
-            ShapeRaw::Circle(c) => c.draw(d, data, pos),
-            ShapeRaw::NoDraw(_) => {}
-            ShapeRaw::Rect(r) => r.draw(d, data, pos),
+            ShapeRaw::Circle(c) => c.evaluate(data, pos),
+            ShapeRaw::NoDraw(n) => n.evaluate(data, pos),
+            ShapeRaw::Rect(r) => r.evaluate(data, pos),
+        }
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        match self {
+            ShapeRaw::Circle(c) => c.children(),
+            ShapeRaw::NoDraw(n) => n.children(),
+            ShapeRaw::Rect(r) => r.children(),
+        }
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        match self {
+            ShapeRaw::Circle(c) => c.children_mut(),
+            ShapeRaw::NoDraw(n) => n.children_mut(),
+            ShapeRaw::Rect(r) => r.children_mut(),
         }
     }
 }
@@ -51,7 +131,12 @@ impl Drawable for ShapeRaw {
 pub struct NoDraw;
 
 impl Drawable for NoDraw {
-    fn draw(&mut self, _d: &mut RaylibDrawHandle, _data: &mut SamplerData, _pos: (i32, i32)) {}
+    fn evaluate(&mut self, _data: &mut SamplerData, pos: (i32, i32)) -> DrawCommand {
+        DrawCommand {
+            position: pos,
+            shape: ResolvedShape::NoDraw,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -65,19 +150,26 @@ pub struct Rect {
 }
 
 impl Drawable for Rect {
-    fn draw(&mut self, d: &mut RaylibDrawHandle, data: &mut SamplerData, pos: (i32, i32)) {
-        d.draw_rectangle_gradient_ex(
-            Rectangle {
-                x: pos.0 as f32,
-                y: pos.1 as f32,
+    fn evaluate(&mut self, data: &mut SamplerData, pos: (i32, i32)) -> DrawCommand {
+        DrawCommand {
+            position: pos,
+            shape: ResolvedShape::Rect {
                 width: self.width.sample(data),
                 height: self.height.sample(data),
+                color_tl: self.color_tl,
+                color_tr: self.color_tr,
+                color_bl: self.color_bl,
+                color_br: self.color_br,
             },
-            self.color_tl,
-            self.color_bl,
-            self.color_br,
-            self.color_tr,
-        );
+        }
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("width", &self.width), child("height", &self.height)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.width, &mut self.height]
     }
 }
 
@@ -0,0 +1,140 @@
+//! Renders every cursor in `cursors/` through a set of scripted inputs and compares the results
+//! against the reference images in `golden/`. Run with `UPDATE_GOLDEN=1` to rewrite the
+//! references after an intentional visual change.
+
+use std::{fs::File, path::Path};
+
+use device_query::Keycode;
+use raylib::prelude::*;
+
+use crate::{
+    gen_shape::Shape,
+    headless::evaluate_frames,
+    scripted_input::{InputEvent, ScriptedInput},
+    software_render::{render_frame, Canvas, RENDER_FPS},
+};
+
+const CURSOR_DIR: &str = "../cursors";
+const GOLDEN_DIR: &str = "golden";
+const IMAGE_SIZE: (u32, u32) = (128, 128);
+const TIMESTAMPS: [f32; 3] = [0.25, 0.5, 1.0];
+
+/// How far a channel may drift before a pixel counts as different.
+const CHANNEL_TOLERANCE: u8 = 3;
+/// How many differing pixels an image may have before it fails.
+const MAX_DIFFERING_PIXELS: usize = 8;
+
+/// The example cursors draw nothing until Escape picks game mode, so most scenarios start by
+/// tapping it. The mouse sits at the center of a 1920x1080 screen, where the buy-mode dot expects
+/// it.
+fn scenarios() -> Vec<(&'static str, ScriptedInput)> {
+    let start = || {
+        ScriptedInput::default()
+            .at(0.0, InputEvent::MoveTo(960, 540))
+            .at(0.0, InputEvent::KeyDown(Keycode::Escape))
+            .at(0.05, InputEvent::KeyUp(Keycode::Escape))
+    };
+    vec![
+        ("idle", start()),
+        (
+            "left_click_held",
+            start().at(0.2, InputEvent::ButtonDown(1)),
+        ),
+        (
+            "right_click",
+            start()
+                .at(0.2, InputEvent::ButtonDown(2))
+                .at(0.3, InputEvent::ButtonUp(2)),
+        ),
+        (
+            "b_then_escape",
+            ScriptedInput::default()
+                .at(0.0, InputEvent::MoveTo(960, 540))
+                .at(0.1, InputEvent::KeyDown(Keycode::B))
+                .at(0.2, InputEvent::KeyUp(Keycode::B))
+                .at(0.4, InputEvent::KeyDown(Keycode::Escape))
+                .at(0.45, InputEvent::KeyUp(Keycode::Escape)),
+        ),
+    ]
+}
+
+fn read_png(path: &Path) -> Canvas {
+    let decoder = png::Decoder::new(File::open(path).unwrap());
+    let mut reader = decoder.read_info().unwrap();
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).unwrap();
+    assert_eq!(info.color_type, png::ColorType::Rgba, "{}", path.display());
+
+    Canvas {
+        width: info.width,
+        height: info.height,
+        pixels: buffer[..info.buffer_size()]
+            .chunks(4)
+            .map(|p| Color::new(p[0], p[1], p[2], p[3]))
+            .collect(),
+    }
+}
+
+fn differing_pixels(a: &Canvas, b: &Canvas) -> usize {
+    let close = |x: u8, y: u8| x.abs_diff(y) <= CHANNEL_TOLERANCE;
+    a.pixels
+        .iter()
+        .zip(&b.pixels)
+        .filter(|(p, q)| {
+            !(close(p.r, q.r) && close(p.g, q.g) && close(p.b, q.b) && close(p.a, q.a))
+        })
+        .count()
+}
+
+#[test]
+fn golden_images() {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let mut failures = Vec::new();
+
+    let mut cursor_files: Vec<_> = std::fs::read_dir(CURSOR_DIR)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|e| e == "jsonc"))
+        .collect();
+    cursor_files.sort();
+    assert!(!cursor_files.is_empty());
+
+    for cursor_file in &cursor_files {
+        let cursor_name = cursor_file.file_stem().unwrap().to_string_lossy();
+        for (scenario, mut input) in scenarios() {
+            let mut cursor: Vec<Shape> =
+                serde_jsonrc::from_reader(File::open(cursor_file).unwrap()).unwrap();
+            let last_frame = (TIMESTAMPS[TIMESTAMPS.len() - 1] * RENDER_FPS).round() as usize;
+            let frames = evaluate_frames(&mut cursor, 1.0 / RENDER_FPS, last_frame, &mut input);
+
+            for time in TIMESTAMPS {
+                let frame = (time * RENDER_FPS).round() as usize;
+                let actual = render_frame(&frames[frame - 1], IMAGE_SIZE);
+                let name = format!("{}-{}-{:.2}s.png", cursor_name, scenario, time);
+                let golden = Path::new(GOLDEN_DIR).join(&name);
+
+                if update {
+                    actual.write_png(&golden).unwrap();
+                    continue;
+                }
+                if !golden.exists() {
+                    failures.push(format!("{}: missing reference image", name));
+                    continue;
+                }
+                let differing = differing_pixels(&actual, &read_png(&golden));
+                if differing > MAX_DIFFERING_PIXELS {
+                    let actual_path = std::env::temp_dir().join(&name);
+                    actual.write_png(&actual_path).unwrap();
+                    failures.push(format!(
+                        "{}: {} pixels differ, actual image written to {}",
+                        name,
+                        differing,
+                        actual_path.display()
+                    ));
+                }
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
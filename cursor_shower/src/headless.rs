@@ -0,0 +1,79 @@
+use crate::{
+    gen_shape::{DrawCommand, Drawable, Shape},
+    input::{InputSnapshot, InputSource},
+    linear_samplers::{Sampler1D, SamplerData},
+    link::link,
+    random_samplers::entropy,
+};
+
+/// Evaluates every enabled shape of `cursor` for the frame described by `data`. The cursor must
+/// have been linked against `data.vars`.
+pub fn evaluate_frame(
+    cursor: &mut [Shape],
+    data: &mut SamplerData,
+    center: (i32, i32),
+) -> Vec<DrawCommand> {
+    let mut commands = Vec::new();
+    for shape in cursor {
+        if shape.enabled.sample(data) >= 1.0 {
+            commands.push(shape.evaluate(data, center));
+        }
+    }
+    commands
+}
+
+/// Runs `cursor` for `frames` frames of `time_step` seconds each without opening a window,
+/// returning the draw commands of every frame. Positions are relative to the cursor center.
+pub fn evaluate_frames(
+    cursor: &mut [Shape],
+    time_step: f32,
+    frames: usize,
+    input: &mut impl InputSource,
+) -> Vec<Vec<DrawCommand>> {
+    let mut data = SamplerData::new();
+    link(cursor, &mut data.vars, entropy());
+    (0..frames)
+        .map(|_| {
+            data.t += time_step;
+            data.input = InputSnapshot::capture(input, &data.input, data.t);
+            evaluate_frame(cursor, &mut data, (0, 0))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gen_shape::ResolvedShape,
+        scripted_input::{InputEvent, ScriptedInput},
+    };
+
+    #[test]
+    fn spinning_dots_frames() {
+        let mut cursor: Vec<Shape> = serde_jsonrc::from_reader(
+            std::fs::File::open("../cursors/spinningDots.jsonc").unwrap(),
+        )
+        .unwrap();
+        let mut input = ScriptedInput::default()
+            .at(0.1, InputEvent::KeyDown(device_query::Keycode::Escape))
+            .at(0.2, InputEvent::KeyUp(device_query::Keycode::Escape));
+
+        let frames = evaluate_frames(&mut cursor, 1.0 / 60.0, 30, &mut input);
+        assert_eq!(frames.len(), 30);
+
+        // Only the bookkeeping shape is enabled before Escape switches to game mode.
+        assert_eq!(frames[0].len(), 1);
+        assert_eq!(frames[0][0].shape, ResolvedShape::NoDraw);
+
+        // In game mode, the center dot sits still and the three dots orbit 40px out.
+        let last = &frames[29];
+        assert_eq!(last.len(), 5);
+        assert_eq!(last[1].position, (0, 0));
+        for dot in &last[2..] {
+            let (x, y) = dot.position;
+            let distance = ((x * x + y * y) as f32).sqrt();
+            assert!((distance - 40.0).abs() < 1.0, "{:?}", dot.position);
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use device_query::{DeviceQuery, DeviceState, Keycode};
+
+#[cfg(target_os = "linux")]
+use crate::linux_scroll::LinuxScroll;
+
+/// Number of mouse button slots reported by device_query. Buttons are 1-based, so index 0 is
+/// always false.
+pub const MOUSE_BUTTON_COUNT: usize = 6;
+
+/// The raw device state reported by an input backend at a point in time.
+#[derive(Debug, Clone)]
+pub struct RawInput {
+    pub buttons: Vec<bool>,
+    pub keys: Vec<Keycode>,
+    pub position: (i32, i32),
+    /// Wheel notches scrolled since the source started, up positive.
+    pub scroll: i32,
+}
+
+impl Default for RawInput {
+    fn default() -> Self {
+        Self {
+            buttons: vec![false; MOUSE_BUTTON_COUNT],
+            keys: Vec::new(),
+            position: (0, 0),
+            scroll: 0,
+        }
+    }
+}
+
+/// Somewhere input comes from, polled once per frame.
+pub trait InputSource {
+    fn poll(&mut self, time: f32) -> RawInput;
+}
+
+/// Raw input can be used directly as a source that never changes.
+impl InputSource for RawInput {
+    fn poll(&mut self, _time: f32) -> RawInput {
+        self.clone()
+    }
+}
+
+/// Reads the real mouse and keyboard through device_query, and on Linux the scroll wheel through
+/// `LinuxScroll`.
+pub struct DeviceQueryInput {
+    device_state: DeviceState,
+    #[cfg(target_os = "linux")]
+    scroll: Option<LinuxScroll>,
+}
+
+impl DeviceQueryInput {
+    pub fn new() -> Self {
+        Self {
+            device_state: DeviceState::new(),
+            #[cfg(target_os = "linux")]
+            scroll: LinuxScroll::open().ok(),
+        }
+    }
+
+    /// Whether the scroll wheel can be read. Without it scrolling always reads as 0.
+    pub fn reads_scroll(&self) -> bool {
+        #[cfg(target_os = "linux")]
+        return self.scroll.is_some();
+        #[cfg(not(target_os = "linux"))]
+        return false;
+    }
+
+    fn scroll(&self) -> i32 {
+        #[cfg(target_os = "linux")]
+        return self.scroll.as_ref().map_or(0, LinuxScroll::total);
+        #[cfg(not(target_os = "linux"))]
+        return 0;
+    }
+}
+
+impl Default for DeviceQueryInput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InputSource for DeviceQueryInput {
+    fn poll(&mut self, _time: f32) -> RawInput {
+        let mouse = self.device_state.get_mouse();
+        RawInput {
+            buttons: mouse.button_pressed,
+            keys: self.device_state.get_keys(),
+            position: mouse.coords,
+            scroll: self.scroll(),
+        }
+    }
+}
+
+/// The state of every input device, captured once per frame so all samplers in that frame agree.
+#[derive(Debug, Clone, Default)]
+pub struct InputSnapshot {
+    pub buttons: Vec<bool>,
+    pub keys: Vec<Keycode>,
+    pub position: (i32, i32),
+    pub delta: (i32, i32),
+    pub scroll: i32,
+    /// Wheel notches scrolled since the previous frame, up positive.
+    pub scroll_delta: i32,
+    pub time: f32,
+    pub delta_time: f32,
+    pub frame: u64,
+}
+
+impl InputSnapshot {
+    pub fn capture(source: &mut impl InputSource, previous: &InputSnapshot, time: f32) -> Self {
+        let raw = source.poll(time);
+
+        // The first snapshot has nothing to be compared against.
+        let (delta, scroll_delta) = if previous.frame == 0 {
+            ((0, 0), 0)
+        } else {
+            (
+                (
+                    raw.position.0 - previous.position.0,
+                    raw.position.1 - previous.position.1,
+                ),
+                raw.scroll - previous.scroll,
+            )
+        };
+
+        Self {
+            buttons: raw.buttons,
+            keys: raw.keys,
+            position: raw.position,
+            delta,
+            scroll: raw.scroll,
+            scroll_delta,
+            time,
+            delta_time: time - previous.time,
+            frame: previous.frame + 1,
+        }
+    }
+
+    pub fn button(&self, button: usize) -> bool {
+        self.buttons.get(button).copied().unwrap_or(false)
+    }
+
+    pub fn key(&self, key: &Keycode) -> bool {
+        self.keys.contains(key)
+    }
+}
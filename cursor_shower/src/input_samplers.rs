@@ -0,0 +1,591 @@
+//! Samplers that read patterns in the mouse and keyboard over time, rather than what is held down
+//! this frame.
+
+use std::collections::VecDeque;
+
+use device_query::Keycode;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    linear_samplers::{child, Child, Movesampler1D, Sampler1D, SamplerData},
+    logic_samplers::{from_bool, is_true},
+    serde_keycode_serialize::{keycode_vec, KeycodeDef, KeycodeDefVec},
+};
+
+fn default_count() -> u32 {
+    2
+}
+
+fn default_max_interval() -> f32 {
+    0.3
+}
+
+/// 1.0 on the frame `mouse_button` is clicked for the `count`th time in a row, with at most
+/// `max_interval` seconds between each click, so a count of 2 is a double click. The clicks after
+/// that start a new run, so holding a rhythm fires every `count` clicks.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct MultiClick {
+    pub mouse_button: usize,
+    #[serde(default = "default_count")]
+    pub count: u32,
+    #[serde(default = "default_max_interval")]
+    pub max_interval: f32,
+    #[serde(skip)]
+    was_down: bool,
+    #[serde(skip)]
+    clicks: u32,
+    #[serde(skip)]
+    since_click: f32,
+}
+
+impl Sampler1D for MultiClick {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let down = data.input.button(self.mouse_button);
+        let clicked = down && !self.was_down;
+        self.was_down = down;
+        self.since_click += data.input.delta_time;
+        if !clicked {
+            return 0.0;
+        }
+
+        if self.clicks > 0 && self.since_click <= self.max_interval {
+            self.clicks += 1;
+        } else {
+            self.clicks = 1;
+        }
+        self.since_click = 0.0;
+        if self.clicks >= self.count {
+            self.clicks = 0;
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+impl From<MultiClick> for Movesampler1D {
+    fn from(m: MultiClick) -> Self {
+        Movesampler1D::MultiClick(m)
+    }
+}
+
+fn default_window() -> f32 {
+    1.0
+}
+
+/// Clicks of `mouse_button` per second, averaged over the last `window` seconds. Spraying reads
+/// high, tapping low.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ClickRate {
+    pub mouse_button: usize,
+    #[serde(default = "default_window")]
+    pub window: f32,
+    #[serde(skip)]
+    was_down: bool,
+    #[serde(skip)]
+    elapsed: f32,
+    #[serde(skip)]
+    clicks: VecDeque<f32>,
+}
+
+impl Sampler1D for ClickRate {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let down = data.input.button(self.mouse_button);
+        self.elapsed += data.input.delta_time;
+        if down && !self.was_down {
+            self.clicks.push_back(self.elapsed);
+        }
+        self.was_down = down;
+
+        while let Some(&oldest) = self.clicks.front() {
+            if self.elapsed - oldest < self.window {
+                break;
+            }
+            self.clicks.pop_front();
+        }
+        self.clicks.len() as f32 / self.window
+    }
+}
+
+impl From<ClickRate> for Movesampler1D {
+    fn from(c: ClickRate) -> Self {
+        Movesampler1D::ClickRate(c)
+    }
+}
+
+/// The mouse's velocity over the last frame, in pixels per second.
+fn mouse_velocity(data: &SamplerData) -> (f32, f32) {
+    let (dx, dy) = data.input.delta;
+    if data.input.delta_time <= 0.0 {
+        return (0.0, 0.0);
+    }
+    (
+        dx as f32 / data.input.delta_time,
+        dy as f32 / data.input.delta_time,
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum Axis {
+    X,
+    Y,
+}
+
+/// How fast the mouse moves along `axis`, in pixels per second. Right and down are positive.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct MouseVelocity {
+    pub axis: Axis,
+}
+
+impl Sampler1D for MouseVelocity {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let (x, y) = mouse_velocity(data);
+        match self.axis {
+            Axis::X => x,
+            Axis::Y => y,
+        }
+    }
+}
+
+impl From<MouseVelocity> for Movesampler1D {
+    fn from(m: MouseVelocity) -> Self {
+        Movesampler1D::MouseVelocity(m)
+    }
+}
+
+/// How fast the mouse moves in any direction, in pixels per second. It jumps around from frame to
+/// frame, so it usually wants a `Smooth` around it.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct MouseSpeed {}
+
+impl Sampler1D for MouseSpeed {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let (x, y) = mouse_velocity(data);
+        x.hypot(y)
+    }
+}
+
+impl From<MouseSpeed> for Movesampler1D {
+    fn from(m: MouseSpeed) -> Self {
+        Movesampler1D::MouseSpeed(m)
+    }
+}
+
+/// How fast the mouse's speed changes, in pixels per second per second. Positive while it speeds
+/// up, negative while it slows down.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct MouseAcceleration {
+    #[serde(skip)]
+    last_speed: Option<f32>,
+}
+
+impl Sampler1D for MouseAcceleration {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let (x, y) = mouse_velocity(data);
+        let speed = x.hypot(y);
+        let last_speed = self.last_speed.replace(speed).unwrap_or(speed);
+        if data.input.delta_time <= 0.0 {
+            return 0.0;
+        }
+        (speed - last_speed) / data.input.delta_time
+    }
+}
+
+impl From<MouseAcceleration> for Movesampler1D {
+    fn from(m: MouseAcceleration) -> Self {
+        Movesampler1D::MouseAcceleration(m)
+    }
+}
+
+fn never() -> Box<Movesampler1D> {
+    Box::new(Movesampler1D::Constant(0.0))
+}
+
+/// How far the mouse has moved in pixels, along the path it took. Goes back to 0 on every frame
+/// `reset` is true.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct MouseDistance {
+    #[serde(default = "never")]
+    pub reset: Box<Movesampler1D>,
+    #[serde(skip)]
+    distance: f32,
+}
+
+impl Sampler1D for MouseDistance {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        if is_true(self.reset.sample(data)) {
+            self.distance = 0.0;
+        } else {
+            let (dx, dy) = data.input.delta;
+            self.distance += (dx as f32).hypot(dy as f32);
+        }
+        self.distance
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("reset", &self.reset)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.reset]
+    }
+}
+
+impl From<MouseDistance> for Movesampler1D {
+    fn from(m: MouseDistance) -> Self {
+        Movesampler1D::MouseDistance(m)
+    }
+}
+
+/// Wheel notches scrolled this frame, up positive.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ScrollDelta {}
+
+impl Sampler1D for ScrollDelta {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        data.input.scroll_delta as f32
+    }
+}
+
+impl From<ScrollDelta> for Movesampler1D {
+    fn from(s: ScrollDelta) -> Self {
+        Movesampler1D::ScrollDelta(s)
+    }
+}
+
+fn default_step() -> f32 {
+    1.0
+}
+
+/// A value moved by `step` for each notch scrolled, up positive, and kept between `min` and `max`.
+/// Starts at `initial`, and goes back to it on every frame `reset` is true.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ScrollValue {
+    pub min: f32,
+    pub max: f32,
+    #[serde(default)]
+    pub initial: f32,
+    #[serde(default = "default_step")]
+    pub step: f32,
+    #[serde(default = "never")]
+    pub reset: Box<Movesampler1D>,
+    #[serde(skip)]
+    value: Option<f32>,
+}
+
+impl Sampler1D for ScrollValue {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let value = if is_true(self.reset.sample(data)) {
+            self.initial
+        } else {
+            let value = self.value.unwrap_or(self.initial);
+            value + data.input.scroll_delta as f32 * self.step
+        };
+        let value = value.max(self.min).min(self.max);
+        self.value = Some(value);
+        value
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("reset", &self.reset)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.reset]
+    }
+}
+
+impl From<ScrollValue> for Movesampler1D {
+    fn from(s: ScrollValue) -> Self {
+        Movesampler1D::ScrollValue(s)
+    }
+}
+
+/// 1.0 while every key in `keys` is down at once, like Ctrl+Shift+X, 0.0 otherwise.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct KeyChord {
+    #[serde(with = "keycode_vec")]
+    #[schemars(with = "KeycodeDefVec")]
+    pub keys: Vec<Keycode>,
+}
+
+impl Sampler1D for KeyChord {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let keys = &self.keys;
+        from_bool(!keys.is_empty() && keys.iter().all(|k| data.input.key(k)))
+    }
+}
+
+impl From<KeyChord> for Movesampler1D {
+    fn from(k: KeyChord) -> Self {
+        Movesampler1D::KeyChord(k)
+    }
+}
+
+fn default_timeout() -> f32 {
+    1.0
+}
+
+/// 1.0 on the frame the last of `keys` is pressed, if they were pressed in order with at most
+/// `timeout` seconds between each, like B then 1. Other keys pressed in between are ignored.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct KeySequence {
+    #[serde(with = "keycode_vec")]
+    #[schemars(with = "KeycodeDefVec")]
+    pub keys: Vec<Keycode>,
+    #[serde(default = "default_timeout")]
+    pub timeout: f32,
+    #[serde(skip)]
+    held: Vec<Keycode>,
+    #[serde(skip)]
+    matched: usize,
+    #[serde(skip)]
+    since_match: f32,
+}
+
+impl Sampler1D for KeySequence {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let keys = &self.keys;
+        self.since_match += data.input.delta_time;
+        if self.matched > 0 && self.since_match > self.timeout {
+            self.matched = 0;
+        }
+
+        let mut fired = false;
+        for key in data.input.keys.iter().filter(|k| keys.contains(k)) {
+            if self.held.contains(key) {
+                continue;
+            }
+            if keys.get(self.matched) == Some(key) {
+                self.matched += 1;
+            } else {
+                // A wrong key can still start the sequence over.
+                self.matched = usize::from(keys.first() == Some(key));
+            }
+            self.since_match = 0.0;
+            if self.matched == keys.len() {
+                self.matched = 0;
+                fired = true;
+            }
+        }
+        self.held = data
+            .input
+            .keys
+            .iter()
+            .filter(|k| keys.contains(k))
+            .copied()
+            .collect();
+        from_bool(fired)
+    }
+}
+
+impl From<KeySequence> for Movesampler1D {
+    fn from(k: KeySequence) -> Self {
+        Movesampler1D::KeySequence(k)
+    }
+}
+
+/// Seconds `key` has been held down, counted from the frame it was pressed, or 0.0 while it is up.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct KeyHeld {
+    #[serde(with = "KeycodeDef")]
+    pub key: Keycode,
+    #[serde(skip)]
+    held: Option<f32>,
+}
+
+impl Sampler1D for KeyHeld {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let down = data.input.key(&self.key);
+        self.held = if down {
+            Some(self.held.map_or(0.0, |held| held + data.input.delta_time))
+        } else {
+            None
+        };
+        self.held.unwrap_or(0.0)
+    }
+}
+
+impl From<KeyHeld> for Movesampler1D {
+    fn from(k: KeyHeld) -> Self {
+        Movesampler1D::KeyHeld(k)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        input::InputSnapshot,
+        scripted_input::{InputEvent, ScriptedInput},
+    };
+
+    /// Samples `sampler` at 8 fps with the left button down on the given frames.
+    fn run(json: &str, frames: usize, down: &[usize]) -> Vec<f32> {
+        let mut sampler: Movesampler1D = serde_jsonrc::from_str(json).unwrap();
+        let mut data = SamplerData::new();
+        data.input.delta_time = 0.125;
+        (0..frames)
+            .map(|frame| {
+                data.input.buttons = vec![false, down.contains(&frame)];
+                sampler.sample(&mut data)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn double_and_triple_clicks() {
+        let double = r#"{ "type": "MultiClick", "mouse_button": 1 }"#;
+        let fired_on = |values: Vec<f32>| -> Vec<usize> {
+            (0..values.len()).filter(|&i| values[i] == 1.0).collect()
+        };
+        // Two quick clicks fire, a third quick one starts over, and a slow pair does not fire.
+        assert_eq!(fired_on(run(double, 20, &[0, 2, 4, 6, 12, 16])), [2, 6]);
+        // Holding the button is one click, not one per frame.
+        assert_eq!(fired_on(run(double, 6, &[0, 1, 2, 3])), Vec::<usize>::new());
+
+        let triple =
+            r#"{ "type": "MultiClick", "mouse_button": 1, "count": 3, "max_interval": 0.25 }"#;
+        assert_eq!(fired_on(run(triple, 10, &[0, 2, 4, 7])), [4]);
+    }
+
+    #[test]
+    fn click_rate_over_a_window() {
+        let rate = r#"{ "type": "ClickRate", "mouse_button": 1, "window": 0.5 }"#;
+        let values = run(rate, 12, &[0, 2, 4, 6]);
+        assert_eq!(values[0], 2.0);
+        assert_eq!(values[2], 4.0);
+        // The first click is half a second old by frame 4, so it has left the window.
+        assert_eq!(values[4], 4.0);
+        assert_eq!(values[6], 4.0);
+        assert_eq!(values[9], 2.0);
+        assert_eq!(values[10], 0.0);
+    }
+
+    #[test]
+    fn mouse_kinematics() {
+        let mut samplers: Vec<Movesampler1D> = serde_jsonrc::from_str(
+            r#"[
+  { "type": "MouseVelocity", "axis": "X" },
+  { "type": "MouseVelocity", "axis": "Y" },
+  { "type": "MouseSpeed" },
+  { "type": "MouseAcceleration" },
+  { "type": "MouseDistance", "reset": { "keys": ["R"] } }
+]"#,
+        )
+        .unwrap();
+        let mut data = SamplerData::new();
+        data.input.delta_time = 0.5;
+        let mut frames = Vec::new();
+        for (delta, reset) in [
+            ((3, -4), false),
+            ((6, 8), false),
+            ((0, 0), true),
+            ((0, 1), false),
+        ] {
+            data.input.delta = delta;
+            data.input.keys = if reset {
+                vec![device_query::Keycode::R]
+            } else {
+                Vec::new()
+            };
+            frames.push(
+                samplers
+                    .iter_mut()
+                    .map(|s| s.sample(&mut data))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        assert_eq!(
+            frames,
+            [
+                [6.0, -8.0, 10.0, 0.0, 5.0],
+                [12.0, 16.0, 20.0, 20.0, 15.0],
+                [0.0, 0.0, 0.0, -40.0, 0.0],
+                [0.0, 2.0, 2.0, 4.0, 1.0],
+            ]
+        );
+    }
+
+    #[test]
+    fn scrolling() {
+        let mut samplers: Vec<Movesampler1D> = serde_jsonrc::from_str(
+            r#"[
+  { "type": "ScrollDelta" },
+  { "type": "ScrollValue", "min": 0.0, "max": 2.0, "initial": 1.0, "step": 0.5 }
+]"#,
+        )
+        .unwrap();
+        let mut input = ScriptedInput::default()
+            .at(0.1, InputEvent::Scroll(1))
+            .at(0.2, InputEvent::Scroll(3))
+            .at(0.25, InputEvent::Scroll(-1))
+            .at(0.3, InputEvent::Scroll(-10));
+        let mut data = SamplerData::new();
+        let mut frames = Vec::new();
+        for frame in 0..4 {
+            data.t = frame as f32 * 0.1;
+            data.input = InputSnapshot::capture(&mut input, &data.input, data.t);
+            frames.push(
+                samplers
+                    .iter_mut()
+                    .map(|s| s.sample(&mut data))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        assert_eq!(frames, [[0.0, 1.0], [1.0, 1.5], [3.0, 2.0], [-11.0, 0.0]]);
+    }
+
+    #[test]
+    fn key_chords_sequences_and_holds() {
+        use device_query::Keycode::{Key1, LControl, LShift, B, X};
+
+        let mut samplers: Vec<Movesampler1D> = serde_jsonrc::from_str(
+            r#"[
+  { "type": "KeyChord", "keys": ["LControl", "LShift", "X"] },
+  { "type": "KeySequence", "keys": ["B", "Key1"], "timeout": 0.5 },
+  { "type": "KeyHeld", "key": "X" }
+]"#,
+        )
+        .unwrap();
+        let mut data = SamplerData::new();
+        data.input.delta_time = 0.25;
+        let mut frames = Vec::new();
+        for keys in [
+            vec![LControl, X],
+            vec![LControl, LShift, X],
+            vec![B],
+            vec![B, Key1],
+            vec![Key1],
+            vec![B],
+            vec![],
+            vec![],
+            vec![Key1],
+        ] {
+            data.input.keys = keys;
+            frames.push(
+                samplers
+                    .iter_mut()
+                    .map(|s| s.sample(&mut data))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        assert_eq!(
+            frames,
+            [
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.25],
+                [0.0, 0.0, 0.0],
+                // Key1 joining a held B completes the sequence once, however long it is held.
+                [0.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0],
+                [0.0, 0.0, 0.0],
+                [0.0, 0.0, 0.0],
+                [0.0, 0.0, 0.0],
+                // Too long after the B.
+                [0.0, 0.0, 0.0],
+            ]
+        );
+    }
+}
@@ -0,0 +1,25 @@
+pub mod animation_samplers;
+pub mod circle;
+pub mod diagnostics;
+pub mod edge_samplers;
+pub mod gen_shape;
+#[cfg(test)]
+mod golden;
+pub mod hash_string;
+pub mod headless;
+pub mod input;
+pub mod input_samplers;
+pub mod linear_samplers;
+pub mod link;
+#[cfg(target_os = "linux")]
+pub mod linux_scroll;
+pub mod loader;
+pub mod logic_samplers;
+pub mod movement;
+pub mod random_samplers;
+pub mod render;
+pub mod scripted_input;
+pub mod serde_keycode_serialize;
+pub mod software_render;
+pub mod validate;
+pub mod variable_holder;
@@ -1,22 +1,93 @@
-use std::str::FromStr;
-
-use device_query::{DeviceQuery, DeviceState, Keycode};
-use schemars::JsonSchema;
+use device_query::Keycode;
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{Schema, SchemaObject, SubschemaValidation},
+    JsonSchema,
+};
 use serde::{Deserialize, Serialize};
 
-use crate::variable_holder::DataHolder;
+use crate::{
+    animation_samplers::{
+        Decay, Ease, Envelope, EnvelopeState, Keyframes, Oscillator, Smooth, Spring,
+    },
+    diagnostics::PathSegment,
+    edge_samplers::{HeldFor, OnPress, OnRelease},
+    input::InputSnapshot,
+    input_samplers::{
+        ClickRate, KeyChord, KeyHeld, KeySequence, MouseAcceleration, MouseDistance, MouseSpeed,
+        MouseVelocity, MultiClick, ScrollDelta, ScrollValue,
+    },
+    logic_samplers::{And, Equal, Greater, If, Less, Not, Or, Xor},
+    random_samplers::{Noise1D, Random},
+    serde_keycode_serialize::{keycode_vec, KeycodeDefVec},
+    variable_holder::DataHolder,
+};
 
 pub struct SamplerData {
     pub t: f32,
     pub vars: DataHolder,
+    pub input: InputSnapshot,
+}
+
+impl SamplerData {
+    pub fn new() -> Self {
+        Self {
+            t: 0.0,
+            vars: DataHolder::new(),
+            input: InputSnapshot::default(),
+        }
+    }
+}
+
+impl Default for SamplerData {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A sampler nested inside another, with the path to it from its parent.
+pub type Child<'a> = (Vec<PathSegment>, &'a Movesampler1D);
+
+pub fn child<'a>(field: &str, sampler: &'a Movesampler1D) -> Child<'a> {
+    (vec![PathSegment::Key(field.to_string())], sampler)
+}
+
+pub fn child_list<'a>(field: &str, samplers: &'a [Movesampler1D]) -> Vec<Child<'a>> {
+    samplers
+        .iter()
+        .enumerate()
+        .map(|(i, sampler)| {
+            (
+                vec![PathSegment::Key(field.to_string()), PathSegment::Index(i)],
+                sampler,
+            )
+        })
+        .collect()
 }
 
 pub trait Sampler1D {
     fn sample(&mut self, data: &mut SamplerData) -> f32;
+
+    /// The samplers this one reads from, for walking a cursor without sampling it.
+    fn children(&self) -> Vec<Child<'_>> {
+        Vec::new()
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        Vec::new()
+    }
+
+    /// Points the sampler at the variable slots in `vars`. Called by `link::link`.
+    fn link(&mut self, _vars: &mut DataHolder) {}
+
+    /// Gives the sampler its own random seed. Called by `link::link`.
+    fn seed(&mut self, _seed: u64) {}
 }
 
-#[derive(Debug, Serialize, Deserialize, JsonSchema)]
-#[serde(untagged)]
+/// A number that changes over time. In a cursor file this is either a plain number, an object
+/// with a `type` naming the sampler (`{"type": "Add", "terms": [...]}`), or, for samplers that
+/// existed before the tagged form, an object recognised by its field names alone.
+#[derive(Debug)]
 pub enum Movesampler1D {
     Constant(f32),
     Time(Time),
@@ -28,7 +99,112 @@ pub enum Movesampler1D {
     Divide(Divide),
     Power(Power),
     Modulo(Modulo),
-    // Trig(Trig),
+    Clamp(Clamp),
+    Min(Min),
+    Max(Max),
+    Abs(Abs),
+    Sign(Sign),
+    Floor(Floor),
+    Ceil(Ceil),
+    Round(Round),
+    Greater(Greater),
+    Less(Less),
+    Equal(Equal),
+    And(And),
+    Or(Or),
+    Xor(Xor),
+    Not(Not),
+    If(If),
+    Ease(Ease),
+    Keyframes(Keyframes),
+    Oscillator(Oscillator),
+    Spring(Spring),
+    Smooth(Smooth),
+    Decay(Decay),
+    OnPress(OnPress),
+    OnRelease(OnRelease),
+    HeldFor(HeldFor),
+    MultiClick(MultiClick),
+    ClickRate(ClickRate),
+    MouseVelocity(MouseVelocity),
+    MouseSpeed(MouseSpeed),
+    MouseAcceleration(MouseAcceleration),
+    MouseDistance(MouseDistance),
+    ScrollDelta(ScrollDelta),
+    ScrollValue(ScrollValue),
+    KeyChord(KeyChord),
+    KeySequence(KeySequence),
+    KeyHeld(KeyHeld),
+    Random(Random),
+    Noise1D(Noise1D),
+    Trig(Trig),
+    MouseClickCounter(MouseClickCounter),
+    CounterReset(CounterReset),
+    Switch(Switch),
+    KeyPress(KeyPress),
+    DeltaTime(DeltaTime),
+    VariableGet(VariableGet),
+    VariableSet(VariableSet),
+    Expressions(Expressions),
+}
+
+/// The tagged form. New samplers only need to be added here, so their field names are free to
+/// overlap with other samplers.
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(remote = "Movesampler1D", tag = "type")]
+#[schemars(rename = "TaggedMovesampler1D")]
+#[allow(dead_code)]
+enum TaggedMovesampler1D {
+    #[serde(skip)]
+    Constant(f32),
+    Time(Time),
+    MouseClick(MouseClick),
+    Map(Map),
+    Add(Add),
+    Subtract(Subtract),
+    Multiply(Multiply),
+    Divide(Divide),
+    Power(Power),
+    Modulo(Modulo),
+    Clamp(Clamp),
+    Min(Min),
+    Max(Max),
+    Abs(Abs),
+    Sign(Sign),
+    Floor(Floor),
+    Ceil(Ceil),
+    Round(Round),
+    Greater(Greater),
+    Less(Less),
+    Equal(Equal),
+    And(And),
+    Or(Or),
+    Xor(Xor),
+    Not(Not),
+    If(If),
+    Ease(Ease),
+    Keyframes(Keyframes),
+    Oscillator(Oscillator),
+    Spring(Spring),
+    Smooth(Smooth),
+    Decay(Decay),
+    OnPress(OnPress),
+    OnRelease(OnRelease),
+    HeldFor(HeldFor),
+    MultiClick(MultiClick),
+    ClickRate(ClickRate),
+    MouseVelocity(MouseVelocity),
+    MouseSpeed(MouseSpeed),
+    MouseAcceleration(MouseAcceleration),
+    MouseDistance(MouseDistance),
+    ScrollDelta(ScrollDelta),
+    ScrollValue(ScrollValue),
+    KeyChord(KeyChord),
+    KeySequence(KeySequence),
+    KeyHeld(KeyHeld),
+    Random(Random),
+    Noise1D(Noise1D),
+    Trig(Trig),
     MouseClickCounter(MouseClickCounter),
     CounterReset(CounterReset),
     Switch(Switch),
@@ -39,6 +215,77 @@ pub enum Movesampler1D {
     Expressions(Expressions),
 }
 
+/// The original untagged form, where variants are told apart by their fields. Order matters, as
+/// the first variant that fits wins.
+#[derive(Deserialize, JsonSchema)]
+#[serde(remote = "Movesampler1D", untagged)]
+#[schemars(rename = "UntaggedMovesampler1D")]
+#[allow(dead_code)]
+enum UntaggedMovesampler1D {
+    Constant(f32),
+    Time(Time),
+    MouseClick(MouseClick),
+    Map(Map),
+    Add(Add),
+    Subtract(Subtract),
+    Multiply(Multiply),
+    Divide(Divide),
+    Power(Power),
+    Modulo(Modulo),
+    MouseClickCounter(MouseClickCounter),
+    CounterReset(CounterReset),
+    Switch(Switch),
+    KeyPress(KeyPress),
+    DeltaTime(DeltaTime),
+    VariableGet(VariableGet),
+    VariableSet(VariableSet),
+    Expressions(Expressions),
+}
+
+impl<'de> Deserialize<'de> for Movesampler1D {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Objects with a `type` are never matched against the untagged form, so a mistake in a
+        // tagged sampler is reported as such instead of falling through to an unrelated sampler.
+        let value = serde_jsonrc::Value::deserialize(deserializer)?;
+        let sampler = if value.get("type").is_some() {
+            TaggedMovesampler1D::deserialize(value)
+        } else {
+            UntaggedMovesampler1D::deserialize(value)
+        };
+        sampler.map_err(serde::de::Error::custom)
+    }
+}
+
+/// Always writes the tagged form, which is what `cursor convert` relies on.
+impl Serialize for Movesampler1D {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Movesampler1D::Constant(constant) => serializer.serialize_f32(*constant),
+            sampler => TaggedMovesampler1D::serialize(sampler, serializer),
+        }
+    }
+}
+
+impl JsonSchema for Movesampler1D {
+    fn schema_name() -> String {
+        "Movesampler1D".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            subschemas: Some(Box::new(SubschemaValidation {
+                any_of: Some(vec![
+                    gen.subschema_for::<TaggedMovesampler1D>(),
+                    gen.subschema_for::<UntaggedMovesampler1D>(),
+                ]),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
 impl Sampler1D for Movesampler1D {
     fn sample(&mut self, data: &mut SamplerData) -> f32 {
         match self {
@@ -52,7 +299,45 @@ impl Sampler1D for Movesampler1D {
             Movesampler1D::Divide(divide) => divide.sample(data),
             Movesampler1D::Power(power) => power.sample(data),
             Movesampler1D::Modulo(modulo) => modulo.sample(data),
-            // Movesampler1D::Trig(trig) => trig.sample(data),
+            Movesampler1D::Clamp(clamp) => clamp.sample(data),
+            Movesampler1D::Min(min) => min.sample(data),
+            Movesampler1D::Max(max) => max.sample(data),
+            Movesampler1D::Abs(abs) => abs.sample(data),
+            Movesampler1D::Sign(sign) => sign.sample(data),
+            Movesampler1D::Floor(floor) => floor.sample(data),
+            Movesampler1D::Ceil(ceil) => ceil.sample(data),
+            Movesampler1D::Round(round) => round.sample(data),
+            Movesampler1D::Greater(greater) => greater.sample(data),
+            Movesampler1D::Less(less) => less.sample(data),
+            Movesampler1D::Equal(equal) => equal.sample(data),
+            Movesampler1D::And(and) => and.sample(data),
+            Movesampler1D::Or(or) => or.sample(data),
+            Movesampler1D::Xor(xor) => xor.sample(data),
+            Movesampler1D::Not(not) => not.sample(data),
+            Movesampler1D::If(if_else) => if_else.sample(data),
+            Movesampler1D::Ease(ease) => ease.sample(data),
+            Movesampler1D::Keyframes(keyframes) => keyframes.sample(data),
+            Movesampler1D::Oscillator(oscillator) => oscillator.sample(data),
+            Movesampler1D::Spring(spring) => spring.sample(data),
+            Movesampler1D::Smooth(smooth) => smooth.sample(data),
+            Movesampler1D::Decay(decay) => decay.sample(data),
+            Movesampler1D::OnPress(on_press) => on_press.sample(data),
+            Movesampler1D::OnRelease(on_release) => on_release.sample(data),
+            Movesampler1D::HeldFor(held_for) => held_for.sample(data),
+            Movesampler1D::MultiClick(multi_click) => multi_click.sample(data),
+            Movesampler1D::ClickRate(click_rate) => click_rate.sample(data),
+            Movesampler1D::MouseVelocity(mouse_velocity) => mouse_velocity.sample(data),
+            Movesampler1D::MouseSpeed(mouse_speed) => mouse_speed.sample(data),
+            Movesampler1D::MouseAcceleration(mouse_acceleration) => mouse_acceleration.sample(data),
+            Movesampler1D::MouseDistance(mouse_distance) => mouse_distance.sample(data),
+            Movesampler1D::ScrollDelta(scroll_delta) => scroll_delta.sample(data),
+            Movesampler1D::ScrollValue(scroll_value) => scroll_value.sample(data),
+            Movesampler1D::KeyChord(key_chord) => key_chord.sample(data),
+            Movesampler1D::KeySequence(key_sequence) => key_sequence.sample(data),
+            Movesampler1D::KeyHeld(key_held) => key_held.sample(data),
+            Movesampler1D::Random(random) => random.sample(data),
+            Movesampler1D::Noise1D(noise) => noise.sample(data),
+            Movesampler1D::Trig(trig) => trig.sample(data),
             Movesampler1D::MouseClickCounter(mouse_click_counter) => {
                 mouse_click_counter.sample(data)
             }
@@ -65,6 +350,150 @@ impl Sampler1D for Movesampler1D {
             Movesampler1D::Expressions(expressions) => expressions.sample(data),
         }
     }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        match self {
+            Movesampler1D::Constant(_) => Vec::new(),
+            Movesampler1D::Time(time) => time.children(),
+            Movesampler1D::MouseClick(mouse_click) => mouse_click.children(),
+            Movesampler1D::Map(map) => map.children(),
+            Movesampler1D::Add(add) => add.children(),
+            Movesampler1D::Subtract(subtract) => subtract.children(),
+            Movesampler1D::Multiply(multiply) => multiply.children(),
+            Movesampler1D::Divide(divide) => divide.children(),
+            Movesampler1D::Power(power) => power.children(),
+            Movesampler1D::Modulo(modulo) => modulo.children(),
+            Movesampler1D::Clamp(clamp) => clamp.children(),
+            Movesampler1D::Min(min) => min.children(),
+            Movesampler1D::Max(max) => max.children(),
+            Movesampler1D::Abs(abs) => abs.children(),
+            Movesampler1D::Sign(sign) => sign.children(),
+            Movesampler1D::Floor(floor) => floor.children(),
+            Movesampler1D::Ceil(ceil) => ceil.children(),
+            Movesampler1D::Round(round) => round.children(),
+            Movesampler1D::Greater(greater) => greater.children(),
+            Movesampler1D::Less(less) => less.children(),
+            Movesampler1D::Equal(equal) => equal.children(),
+            Movesampler1D::And(and) => and.children(),
+            Movesampler1D::Or(or) => or.children(),
+            Movesampler1D::Xor(xor) => xor.children(),
+            Movesampler1D::Not(not) => not.children(),
+            Movesampler1D::If(if_else) => if_else.children(),
+            Movesampler1D::Ease(ease) => ease.children(),
+            Movesampler1D::Keyframes(keyframes) => keyframes.children(),
+            Movesampler1D::Oscillator(oscillator) => oscillator.children(),
+            Movesampler1D::Spring(spring) => spring.children(),
+            Movesampler1D::Smooth(smooth) => smooth.children(),
+            Movesampler1D::Decay(decay) => decay.children(),
+            Movesampler1D::OnPress(on_press) => on_press.children(),
+            Movesampler1D::OnRelease(on_release) => on_release.children(),
+            Movesampler1D::HeldFor(held_for) => held_for.children(),
+            Movesampler1D::MultiClick(multi_click) => multi_click.children(),
+            Movesampler1D::ClickRate(click_rate) => click_rate.children(),
+            Movesampler1D::MouseVelocity(mouse_velocity) => mouse_velocity.children(),
+            Movesampler1D::MouseSpeed(mouse_speed) => mouse_speed.children(),
+            Movesampler1D::MouseAcceleration(mouse_acceleration) => mouse_acceleration.children(),
+            Movesampler1D::MouseDistance(mouse_distance) => mouse_distance.children(),
+            Movesampler1D::ScrollDelta(scroll_delta) => scroll_delta.children(),
+            Movesampler1D::ScrollValue(scroll_value) => scroll_value.children(),
+            Movesampler1D::KeyChord(key_chord) => key_chord.children(),
+            Movesampler1D::KeySequence(key_sequence) => key_sequence.children(),
+            Movesampler1D::KeyHeld(key_held) => key_held.children(),
+            Movesampler1D::Random(random) => random.children(),
+            Movesampler1D::Noise1D(noise) => noise.children(),
+            Movesampler1D::Trig(trig) => trig.children(),
+            Movesampler1D::MouseClickCounter(mouse_click_counter) => mouse_click_counter.children(),
+            Movesampler1D::CounterReset(counter_reset) => counter_reset.children(),
+            Movesampler1D::Switch(switch) => switch.children(),
+            Movesampler1D::KeyPress(key_press) => key_press.children(),
+            Movesampler1D::DeltaTime(delta_time) => delta_time.children(),
+            Movesampler1D::VariableGet(variable_get) => variable_get.children(),
+            Movesampler1D::VariableSet(variable_set) => variable_set.children(),
+            Movesampler1D::Expressions(expressions) => expressions.children(),
+        }
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        match self {
+            Movesampler1D::Constant(_) => Vec::new(),
+            Movesampler1D::Time(time) => time.children_mut(),
+            Movesampler1D::MouseClick(mouse_click) => mouse_click.children_mut(),
+            Movesampler1D::Map(map) => map.children_mut(),
+            Movesampler1D::Add(add) => add.children_mut(),
+            Movesampler1D::Subtract(subtract) => subtract.children_mut(),
+            Movesampler1D::Multiply(multiply) => multiply.children_mut(),
+            Movesampler1D::Divide(divide) => divide.children_mut(),
+            Movesampler1D::Power(power) => power.children_mut(),
+            Movesampler1D::Modulo(modulo) => modulo.children_mut(),
+            Movesampler1D::Clamp(clamp) => clamp.children_mut(),
+            Movesampler1D::Min(min) => min.children_mut(),
+            Movesampler1D::Max(max) => max.children_mut(),
+            Movesampler1D::Abs(abs) => abs.children_mut(),
+            Movesampler1D::Sign(sign) => sign.children_mut(),
+            Movesampler1D::Floor(floor) => floor.children_mut(),
+            Movesampler1D::Ceil(ceil) => ceil.children_mut(),
+            Movesampler1D::Round(round) => round.children_mut(),
+            Movesampler1D::Greater(greater) => greater.children_mut(),
+            Movesampler1D::Less(less) => less.children_mut(),
+            Movesampler1D::Equal(equal) => equal.children_mut(),
+            Movesampler1D::And(and) => and.children_mut(),
+            Movesampler1D::Or(or) => or.children_mut(),
+            Movesampler1D::Xor(xor) => xor.children_mut(),
+            Movesampler1D::Not(not) => not.children_mut(),
+            Movesampler1D::If(if_else) => if_else.children_mut(),
+            Movesampler1D::Ease(ease) => ease.children_mut(),
+            Movesampler1D::Keyframes(keyframes) => keyframes.children_mut(),
+            Movesampler1D::Oscillator(oscillator) => oscillator.children_mut(),
+            Movesampler1D::Spring(spring) => spring.children_mut(),
+            Movesampler1D::Smooth(smooth) => smooth.children_mut(),
+            Movesampler1D::Decay(decay) => decay.children_mut(),
+            Movesampler1D::OnPress(on_press) => on_press.children_mut(),
+            Movesampler1D::OnRelease(on_release) => on_release.children_mut(),
+            Movesampler1D::HeldFor(held_for) => held_for.children_mut(),
+            Movesampler1D::MultiClick(multi_click) => multi_click.children_mut(),
+            Movesampler1D::ClickRate(click_rate) => click_rate.children_mut(),
+            Movesampler1D::MouseVelocity(mouse_velocity) => mouse_velocity.children_mut(),
+            Movesampler1D::MouseSpeed(mouse_speed) => mouse_speed.children_mut(),
+            Movesampler1D::MouseAcceleration(mouse_acceleration) => {
+                mouse_acceleration.children_mut()
+            }
+            Movesampler1D::MouseDistance(mouse_distance) => mouse_distance.children_mut(),
+            Movesampler1D::ScrollDelta(scroll_delta) => scroll_delta.children_mut(),
+            Movesampler1D::ScrollValue(scroll_value) => scroll_value.children_mut(),
+            Movesampler1D::KeyChord(key_chord) => key_chord.children_mut(),
+            Movesampler1D::KeySequence(key_sequence) => key_sequence.children_mut(),
+            Movesampler1D::KeyHeld(key_held) => key_held.children_mut(),
+            Movesampler1D::Random(random) => random.children_mut(),
+            Movesampler1D::Noise1D(noise) => noise.children_mut(),
+            Movesampler1D::Trig(trig) => trig.children_mut(),
+            Movesampler1D::MouseClickCounter(mouse_click_counter) => {
+                mouse_click_counter.children_mut()
+            }
+            Movesampler1D::CounterReset(counter_reset) => counter_reset.children_mut(),
+            Movesampler1D::Switch(switch) => switch.children_mut(),
+            Movesampler1D::KeyPress(key_press) => key_press.children_mut(),
+            Movesampler1D::DeltaTime(delta_time) => delta_time.children_mut(),
+            Movesampler1D::VariableGet(variable_get) => variable_get.children_mut(),
+            Movesampler1D::VariableSet(variable_set) => variable_set.children_mut(),
+            Movesampler1D::Expressions(expressions) => expressions.children_mut(),
+        }
+    }
+
+    fn link(&mut self, vars: &mut DataHolder) {
+        match self {
+            Movesampler1D::VariableGet(variable_get) => variable_get.link(vars),
+            Movesampler1D::VariableSet(variable_set) => variable_set.link(vars),
+            _ => {}
+        }
+    }
+
+    fn seed(&mut self, seed: u64) {
+        match self {
+            Movesampler1D::Random(random) => random.seed(seed),
+            Movesampler1D::Noise1D(noise) => noise.seed(seed),
+            _ => {}
+        }
+    }
 }
 
 // #[derive(Debug, Serialize, Deserialize,JsonSchema)]
@@ -103,28 +532,59 @@ impl From<Time> for Movesampler1D {
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct MouseClick {
-    pub mouse_timer_decrease: Box<Movesampler1D>,
+    /// Subtracted from the value every frame, which is why cursors feed it a `DeltaTime`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mouse_timer_decrease: Option<Box<Movesampler1D>>,
     pub mouse_button: usize,
+    /// Ignore clicks until the last one has finished.
     pub force_full_cycle: bool,
+    /// Times the click in seconds instead of using `mouse_timer_decrease`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub envelope: Option<Envelope>,
     #[serde(skip)]
     value: f32,
+    #[serde(skip)]
+    state: EnvelopeState,
 }
 
 impl Sampler1D for MouseClick {
     fn sample(&mut self, data: &mut SamplerData) -> f32 {
-        let device_state = DeviceState::new();
-        let mouse = device_state.get_mouse();
-        if mouse.button_pressed[self.mouse_button] && (!self.force_full_cycle || self.value <= 0.0)
-        {
+        let pressed = data.input.button(self.mouse_button);
+        if let Some(envelope) = &self.envelope {
+            return self.state.step(
+                envelope,
+                pressed,
+                !self.force_full_cycle,
+                data.input.delta_time,
+            );
+        }
+
+        if pressed && (!self.force_full_cycle || self.value <= 0.0) {
             self.value = 1.0;
         }
 
-        if self.value > 0.0 {
-            self.value -= self.mouse_timer_decrease.sample(data);
+        if let Some(decrease) = &mut self.mouse_timer_decrease {
+            if self.value > 0.0 {
+                self.value -= decrease.sample(data);
+            }
         }
 
         self.value
     }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        self.mouse_timer_decrease
+            .iter()
+            .map(|decrease| child("mouse_timer_decrease", decrease))
+            .collect()
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        self.mouse_timer_decrease
+            .iter_mut()
+            .map(|decrease| &mut **decrease)
+            .collect()
+    }
 }
 
 impl From<MouseClick> for Movesampler1D {
@@ -148,8 +608,15 @@ impl Sampler1D for Map {
         let before_range = self.before_max - self.before_min;
         let after_range = self.after_max - self.after_min;
         let before_normalized = (before - self.before_min) / before_range;
-        let after = (before_normalized * after_range) + self.after_min;
-        after
+        (before_normalized * after_range) + self.after_min
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("sampler", &self.sampler)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.sampler]
     }
 }
 
@@ -170,6 +637,14 @@ impl Sampler1D for Add {
             .iter_mut()
             .fold(0.0, |acc, s| acc + s.sample(data))
     }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        child_list("terms", &self.terms)
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        self.terms.iter_mut().collect()
+    }
 }
 
 impl From<Add> for Movesampler1D {
@@ -188,6 +663,14 @@ impl Sampler1D for Subtract {
     fn sample(&mut self, data: &mut SamplerData) -> f32 {
         self.pos.sample(data) - self.neg.sample(data)
     }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("pos", &self.pos), child("neg", &self.neg)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.pos, &mut self.neg]
+    }
 }
 
 impl From<Subtract> for Movesampler1D {
@@ -207,6 +690,14 @@ impl Sampler1D for Multiply {
             .iter_mut()
             .fold(1.0, |acc, s| acc * s.sample(data))
     }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        child_list("factors", &self.factors)
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        self.factors.iter_mut().collect()
+    }
 }
 
 impl From<Multiply> for Movesampler1D {
@@ -225,6 +716,14 @@ impl Sampler1D for Divide {
     fn sample(&mut self, data: &mut SamplerData) -> f32 {
         self.top.sample(data) / self.bottom.sample(data)
     }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("top", &self.top), child("bottom", &self.bottom)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.top, &mut self.bottom]
+    }
 }
 
 impl From<Divide> for Movesampler1D {
@@ -243,6 +742,14 @@ impl Sampler1D for Power {
     fn sample(&mut self, data: &mut SamplerData) -> f32 {
         self.base.sample(data).powf(self.exponent.sample(data))
     }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("base", &self.base), child("exponent", &self.exponent)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.base, &mut self.exponent]
+    }
 }
 
 impl From<Power> for Movesampler1D {
@@ -261,6 +768,14 @@ impl Sampler1D for Modulo {
     fn sample(&mut self, data: &mut SamplerData) -> f32 {
         self.base.sample(data) % self.divisor.sample(data)
     }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("base", &self.base), child("divisor", &self.divisor)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.base, &mut self.divisor]
+    }
 }
 
 impl From<Modulo> for Movesampler1D {
@@ -269,51 +784,335 @@ impl From<Modulo> for Movesampler1D {
     }
 }
 
-// #[derive(Debug, Serialize, Deserialize,JsonSchema)]
-// #[serde(tag = "type")]
-// pub enum Trig {
-//     Sin(Box<Movesampler1D>),
-//     Cos(Box<Movesampler1D>),
-//     Tan(Box<Movesampler1D>),
-//     Asin(Box<Movesampler1D>),
-//     Acos(Box<Movesampler1D>),
-//     Atan(Box<Movesampler1D>),
-// }
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Clamp {
+    pub value: Box<Movesampler1D>,
+    pub min: Box<Movesampler1D>,
+    pub max: Box<Movesampler1D>,
+}
 
-// impl Sampler1D for Trig {
-//     fn sample(&mut self, data: &mut SamplerData) -> f32 {
-//         match self {
-//             Trig::Sin(s) => s.sample(t).sin(),
-//             Trig::Cos(s) => s.sample(t).cos(),
-//             Trig::Tan(s) => s.sample(t).tan(),
-//             Trig::Asin(s) => s.sample(t).asin(),
-//             Trig::Acos(s) => s.sample(t).acos(),
-//             Trig::Atan(s) => s.sample(t).atan(),
-//         }
-//     }
-// }
+impl Sampler1D for Clamp {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let value = self.value.sample(data);
+        let min = self.min.sample(data);
+        let max = self.max.sample(data);
+        // Unlike f32::clamp, a min above max doesn't panic; max wins.
+        value.max(min).min(max)
+    }
 
-// impl From<Trig> for Movesampler1D {
-//     fn from(t: Trig) -> Self {
-//         Movesampler1D::Trig(t)
-//     }
-// }
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![
+            child("value", &self.value),
+            child("min", &self.min),
+            child("max", &self.max),
+        ]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.value, &mut self.min, &mut self.max]
+    }
+}
+
+impl From<Clamp> for Movesampler1D {
+    fn from(c: Clamp) -> Self {
+        Movesampler1D::Clamp(c)
+    }
+}
 
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Min {
+    pub values: Vec<Movesampler1D>,
+}
+
+impl Sampler1D for Min {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        self.values
+            .iter_mut()
+            .fold(f32::INFINITY, |acc, s| acc.min(s.sample(data)))
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        child_list("values", &self.values)
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        self.values.iter_mut().collect()
+    }
+}
+
+impl From<Min> for Movesampler1D {
+    fn from(m: Min) -> Self {
+        Movesampler1D::Min(m)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Max {
+    pub values: Vec<Movesampler1D>,
+}
+
+impl Sampler1D for Max {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        self.values
+            .iter_mut()
+            .fold(f32::NEG_INFINITY, |acc, s| acc.max(s.sample(data)))
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        child_list("values", &self.values)
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        self.values.iter_mut().collect()
+    }
+}
+
+impl From<Max> for Movesampler1D {
+    fn from(m: Max) -> Self {
+        Movesampler1D::Max(m)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Abs {
+    pub value: Box<Movesampler1D>,
+}
+
+impl Sampler1D for Abs {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let value = self.value.sample(data);
+        value.abs()
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("value", &self.value)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.value]
+    }
+}
+
+impl From<Abs> for Movesampler1D {
+    fn from(a: Abs) -> Self {
+        Movesampler1D::Abs(a)
+    }
+}
+
+/// -1, 0 or 1. Unlike f32::signum, zero stays zero.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Sign {
+    pub value: Box<Movesampler1D>,
+}
+
+impl Sampler1D for Sign {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let value = self.value.sample(data);
+        if value > 0.0 {
+            1.0
+        } else if value < 0.0 {
+            -1.0
+        } else {
+            0.0
+        }
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("value", &self.value)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.value]
+    }
+}
+
+impl From<Sign> for Movesampler1D {
+    fn from(s: Sign) -> Self {
+        Movesampler1D::Sign(s)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Floor {
+    pub value: Box<Movesampler1D>,
+}
+
+impl Sampler1D for Floor {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let value = self.value.sample(data);
+        value.floor()
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("value", &self.value)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.value]
+    }
+}
+
+impl From<Floor> for Movesampler1D {
+    fn from(f: Floor) -> Self {
+        Movesampler1D::Floor(f)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Ceil {
+    pub value: Box<Movesampler1D>,
+}
+
+impl Sampler1D for Ceil {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let value = self.value.sample(data);
+        value.ceil()
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("value", &self.value)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.value]
+    }
+}
+
+impl From<Ceil> for Movesampler1D {
+    fn from(c: Ceil) -> Self {
+        Movesampler1D::Ceil(c)
+    }
+}
+
+/// Rounds half-way values away from zero.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Round {
+    pub value: Box<Movesampler1D>,
+}
+
+impl Sampler1D for Round {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let value = self.value.sample(data);
+        value.round()
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("value", &self.value)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.value]
+    }
+}
+
+impl From<Round> for Movesampler1D {
+    fn from(r: Round) -> Self {
+        Movesampler1D::Round(r)
+    }
+}
+
+/// Trigonometry on a sampler, in radians. Written as `{"type": "Trig", "function": "Sin", ...}`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "function")]
+pub enum Trig {
+    Sin {
+        value: Box<Movesampler1D>,
+    },
+    Cos {
+        value: Box<Movesampler1D>,
+    },
+    Tan {
+        value: Box<Movesampler1D>,
+    },
+    Asin {
+        value: Box<Movesampler1D>,
+    },
+    Acos {
+        value: Box<Movesampler1D>,
+    },
+    Atan {
+        value: Box<Movesampler1D>,
+    },
+    /// The angle of the point `(x, y)`, from -pi to pi.
+    Atan2 {
+        y: Box<Movesampler1D>,
+        x: Box<Movesampler1D>,
+    },
+    ToDegrees {
+        value: Box<Movesampler1D>,
+    },
+    ToRadians {
+        value: Box<Movesampler1D>,
+    },
+}
+
+impl Sampler1D for Trig {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        match self {
+            Trig::Sin { value } => value.sample(data).sin(),
+            Trig::Cos { value } => value.sample(data).cos(),
+            Trig::Tan { value } => value.sample(data).tan(),
+            Trig::Asin { value } => value.sample(data).asin(),
+            Trig::Acos { value } => value.sample(data).acos(),
+            Trig::Atan { value } => value.sample(data).atan(),
+            Trig::Atan2 { y, x } => y.sample(data).atan2(x.sample(data)),
+            Trig::ToDegrees { value } => value.sample(data).to_degrees(),
+            Trig::ToRadians { value } => value.sample(data).to_radians(),
+        }
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        match self {
+            Trig::Atan2 { y, x } => vec![child("y", y), child("x", x)],
+            Trig::Sin { value }
+            | Trig::Cos { value }
+            | Trig::Tan { value }
+            | Trig::Asin { value }
+            | Trig::Acos { value }
+            | Trig::Atan { value }
+            | Trig::ToDegrees { value }
+            | Trig::ToRadians { value } => vec![child("value", value)],
+        }
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        match self {
+            Trig::Atan2 { y, x } => vec![y, x],
+            Trig::Sin { value }
+            | Trig::Cos { value }
+            | Trig::Tan { value }
+            | Trig::Asin { value }
+            | Trig::Acos { value }
+            | Trig::Atan { value }
+            | Trig::ToDegrees { value }
+            | Trig::ToRadians { value } => vec![value],
+        }
+    }
+}
+
+impl From<Trig> for Movesampler1D {
+    fn from(t: Trig) -> Self {
+        Movesampler1D::Trig(t)
+    }
+}
+
+/// Counts presses of a button. Holding it down counts once.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct MouseClickCounter {
     pub mouse_click_counter_button: usize,
     #[serde(skip)]
     counter: u32,
+    #[serde(skip)]
+    was_down: bool,
 }
 
 impl Sampler1D for MouseClickCounter {
-    fn sample(&mut self, _data: &mut SamplerData) -> f32 {
-        let device_state = DeviceState::new();
-        let mouse = device_state.get_mouse();
-
-        if mouse.button_pressed[self.mouse_click_counter_button] {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let down = data.input.button(self.mouse_click_counter_button);
+        if down && !self.was_down {
             self.counter += 1;
         }
+        self.was_down = down;
         self.counter as f32
     }
 }
@@ -335,10 +1134,19 @@ pub struct CounterReset {
 impl Sampler1D for CounterReset {
     fn sample(&mut self, data: &mut SamplerData) -> f32 {
         let reset = self.reset.sample(data);
+        let counter = self.counter.sample(data);
         if reset >= 1.0 {
-            self.offset = self.counter.sample(data);
+            self.offset = counter;
         }
-        self.counter.sample(data) - self.offset
+        counter - self.offset
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("counter", &self.counter), child("reset", &self.reset)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.counter, &mut self.reset]
     }
 }
 
@@ -368,31 +1176,31 @@ impl Sampler1D for Switch {
         }
         self.enabled
     }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![
+            child("enable", &self.enable),
+            child("disable", &self.disable),
+        ]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.enable, &mut self.disable]
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct KeyPress {
-    pub keys: Vec<String>,
-    #[serde(skip)]
-    fixed_keys: Vec<Keycode>,
+    #[serde(with = "keycode_vec")]
+    #[schemars(with = "KeycodeDefVec")]
+    pub keys: Vec<Keycode>,
 }
 
 impl Sampler1D for KeyPress {
-    fn sample(&mut self, _data: &mut SamplerData) -> f32 {
-        if self.fixed_keys.len() == 0 {
-            self.fixed_keys = self
-                .keys
-                .iter()
-                .map(|k| Keycode::from_str(k).expect("Invalid key"))
-                .collect();
-        }
-
-        let device_state = DeviceState::new();
-        let keyboard = device_state.get_keys();
-
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
         let mut pressed = 0.0;
-        for k in self.fixed_keys.iter() {
-            if keyboard.contains(k) {
+        for k in self.keys.iter() {
+            if data.input.key(k) {
                 pressed += 1.0;
             }
         }
@@ -437,11 +1245,12 @@ pub struct VariableGet {
 
 impl Sampler1D for VariableGet {
     fn sample(&mut self, data: &mut SamplerData) -> f32 {
-        if self.variable_id.is_none() {
-            self.variable_id = Some(data.vars.add_key(&self.variable_name));
-        }
+        data.vars
+            .get(self.variable_id.expect("cursor was not linked"))
+    }
 
-        data.vars.get(self.variable_id.unwrap())
+    fn link(&mut self, vars: &mut DataHolder) {
+        self.variable_id = Some(vars.add_key(&self.variable_name));
     }
 }
 
@@ -461,17 +1270,26 @@ pub struct VariableSet {
 
 impl Sampler1D for VariableSet {
     fn sample(&mut self, data: &mut SamplerData) -> f32 {
-        if self.variable_id.is_none() {
-            self.variable_id = Some(data.vars.add_key(&self.set_variable_name));
-        }
-
         let value = self.value.sample(data);
 
-        data.vars.set(self.variable_id.unwrap(), value);
+        data.vars
+            .set(self.variable_id.expect("cursor was not linked"), value);
         // println!("Set variable {} to {}", self.set_variable_name, value);
 
         value
     }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("value", &self.value)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.value]
+    }
+
+    fn link(&mut self, vars: &mut DataHolder) {
+        self.variable_id = Some(vars.add_key(&self.set_variable_name));
+    }
 }
 
 impl From<VariableSet> for Movesampler1D {
@@ -492,6 +1310,14 @@ impl Sampler1D for Expressions {
             .iter_mut()
             .fold(0.0, |_acc, e| e.sample(data))
     }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        child_list("expressions", &self.expressions)
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        self.expressions.iter_mut().collect()
+    }
 }
 
 impl From<Expressions> for Movesampler1D {
@@ -510,10 +1336,12 @@ mod tests {
         let sampler = Movesampler1D::Multiply(Multiply {
             factors: vec![
                 Movesampler1D::MouseClick(MouseClick {
-                    mouse_timer_decrease: Box::new(Movesampler1D::Constant(0.01)),
+                    mouse_timer_decrease: Some(Box::new(Movesampler1D::Constant(0.01))),
                     mouse_button: 1,
                     force_full_cycle: false,
+                    envelope: None,
                     value: 0.0,
+                    state: EnvelopeState::default(),
                 }),
                 Movesampler1D::Constant(50.0),
             ],
@@ -521,4 +1349,111 @@ mod tests {
 
         println!("{:?}", serde_jsonrc::to_string(&sampler).unwrap());
     }
+
+    #[test]
+    fn tagged_and_untagged_forms() {
+        let untagged: Movesampler1D =
+            serde_jsonrc::from_str(r#"{ "terms": [1.0, { "speed": 2.0 }] }"#).unwrap();
+        let tagged: Movesampler1D = serde_jsonrc::from_str(
+            r#"{ "type": "Add", "terms": [1.0, { "type": "Time", "speed": 2.0 }] }"#,
+        )
+        .unwrap();
+
+        let expected = r#"{"type":"Add","terms":[1.0,{"type":"Time","speed":2.0}]}"#;
+        assert_eq!(serde_jsonrc::to_string(&untagged).unwrap(), expected);
+        assert_eq!(serde_jsonrc::to_string(&tagged).unwrap(), expected);
+
+        // A tag means the fields are only checked against that sampler.
+        assert!(
+            serde_jsonrc::from_str::<Movesampler1D>(r#"{ "type": "Time", "terms": [] }"#).is_err()
+        );
+    }
+
+    #[test]
+    fn trig() {
+        let mut sampler: Movesampler1D = serde_jsonrc::from_str(
+            r#"{
+  "type": "Trig",
+  "function": "ToDegrees",
+  "value": {
+    "type": "Trig",
+    "function": "Atan2",
+    "y": { "type": "Trig", "function": "Sin", "value": 1.5707964 },
+    "x": 1.0
+  }
+}"#,
+        )
+        .unwrap();
+        assert!((sampler.sample(&mut SamplerData::new()) - 45.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn clamped_and_rounded_bloom() {
+        // A click that decays past zero, clamped to [0, 1] and scaled to whole pixels.
+        let mut sampler: Movesampler1D = serde_jsonrc::from_str(
+            r#"{
+  "type": "Round",
+  "value": {
+    "factors": [
+      10.5,
+      {
+        "type": "Clamp",
+        "value": { "mouse_timer_decrease": 0.3, "mouse_button": 1, "force_full_cycle": false },
+        "min": 0.0,
+        "max": 1.0
+      }
+    ]
+  }
+}"#,
+        )
+        .unwrap();
+        let mut data = SamplerData::new();
+        data.input.buttons = vec![false, true];
+        assert_eq!(sampler.sample(&mut data), 7.0);
+        data.input.buttons = vec![false, false];
+        let values: Vec<f32> = (0..4).map(|_| sampler.sample(&mut data)).collect();
+        assert_eq!(values, [4.0, 1.0, 0.0, 0.0]);
+
+        let mut bounded: Movesampler1D = serde_jsonrc::from_str(
+            r#"{ "type": "Max", "values": [{ "type": "Sign", "value": 0.0 }, { "type": "Min", "values": [-2.5, 3.0] }] }"#,
+        )
+        .unwrap();
+        assert_eq!(bounded.sample(&mut data), 0.0);
+    }
+
+    #[test]
+    fn input_samplers_read_snapshot() {
+        let mut data = SamplerData {
+            t: 0.0,
+            vars: DataHolder::new(),
+            input: InputSnapshot {
+                buttons: vec![false, true, false],
+                keys: vec![Keycode::B],
+                ..Default::default()
+            },
+        };
+
+        let mut click = MouseClick {
+            mouse_timer_decrease: Some(Box::new(Movesampler1D::Constant(0.25))),
+            mouse_button: 1,
+            force_full_cycle: false,
+            envelope: None,
+            value: 0.0,
+            state: EnvelopeState::default(),
+        };
+        assert_eq!(click.sample(&mut data), 0.75);
+
+        let mut keys = KeyPress {
+            keys: vec![Keycode::B, Keycode::Escape],
+        };
+        assert_eq!(keys.sample(&mut data), 1.0);
+
+        // Buttons the snapshot knows nothing about read as released.
+        let mut counter = MouseClickCounter {
+            mouse_click_counter_button: 5,
+            counter: 0,
+            was_down: false,
+        };
+        assert_eq!(counter.sample(&mut data), 0.0);
+    }
 }
@@ -0,0 +1,170 @@
+//! Resolves variable names to slots in a `DataHolder` before a cursor runs, so sampling never has
+//! to look a name up or allocate.
+
+use std::collections::HashSet;
+
+use crate::{
+    diagnostics::{JsonPath, PathSegment},
+    gen_shape::{Drawable, Shape},
+    linear_samplers::{Movesampler1D, Sampler1D},
+    random_samplers::Rng,
+    validate::Problem,
+    variable_holder::DataHolder,
+};
+
+/// Every sampler in the order it is sampled within a frame: children before their parent, and
+/// shapes in file order.
+fn evaluation_order<'a>(
+    path: Vec<PathSegment>,
+    sampler: &'a Movesampler1D,
+    order: &mut Vec<(Vec<PathSegment>, &'a Movesampler1D)>,
+) {
+    for (child_path, child) in sampler.children() {
+        evaluation_order([&path[..], &child_path].concat(), child, order);
+    }
+    order.push((path, sampler));
+}
+
+/// The seed for whatever sits at `path` below something seeded with `seed`. Depending only on
+/// the path means adding a sampler elsewhere does not reshuffle the others.
+fn derive_seed(seed: u64, path: &[PathSegment]) -> u64 {
+    path.iter().fold(seed, |seed, segment| match segment {
+        PathSegment::Index(i) => {
+            Rng::new(seed ^ (*i as u64).wrapping_mul(0xD6E8_FEB8_6659_FD93)).next_u64()
+        }
+        PathSegment::Key(key) => key.bytes().fold(Rng::new(!seed).next_u64(), |seed, b| {
+            Rng::new(seed ^ b as u64).next_u64()
+        }),
+    })
+}
+
+/// Links and seeds `sampler` and everything below it. `children` and `children_mut` list the
+/// same samplers in the same order, which is what pairs each child with its path.
+fn link_sampler(sampler: &mut Movesampler1D, seed: u64, vars: &mut DataHolder) {
+    sampler.link(vars);
+    sampler.seed(seed);
+    let children: Vec<(Vec<PathSegment>, *const Movesampler1D)> = sampler
+        .children()
+        .into_iter()
+        .map(|(path, child)| (path, child as *const _))
+        .collect();
+    for ((path, expected), child) in children.into_iter().zip(sampler.children_mut()) {
+        debug_assert!(std::ptr::eq(expected, child), "children out of order");
+        link_sampler(child, derive_seed(seed, &path), vars);
+    }
+}
+
+/// Gives every variable in `cursor` a slot in `vars`, in the order they are first used. Names
+/// already in `vars` keep their slot and value, so a reloaded cursor can be linked against the
+/// data of the one it replaces.
+///
+/// Returns the variables that are read before anything sets them in the same frame. Those read
+/// the previous frame's value, or 0.0 on the first frame, which is usually a mistake.
+///
+/// Also seeds every sampler from its shape's `seed` and its path within the shape. Shapes without
+/// a `seed` use one derived from `unseeded` and their index, so passing the same `unseeded` to
+/// every reload keeps their random values from jumping.
+pub fn link(cursor: &mut [Shape], vars: &mut DataHolder, unseeded: u64) -> Vec<Problem> {
+    let mut order = Vec::new();
+    for (i, shape) in cursor.iter().enumerate() {
+        for (path, sampler) in shape.children() {
+            evaluation_order(
+                [vec![PathSegment::Index(i)], path].concat(),
+                sampler,
+                &mut order,
+            );
+        }
+    }
+
+    let mut problems = Vec::new();
+    let mut written = HashSet::new();
+    let mut reported = HashSet::new();
+    for (path, sampler) in order {
+        match sampler {
+            Movesampler1D::VariableGet(get) => {
+                vars.add_key(&get.variable_name);
+                if !written.contains(&get.variable_name) && reported.insert(&get.variable_name) {
+                    problems.push(Problem {
+                        path: JsonPath(path),
+                        message: format!(
+                            "variable `{}` is read before it is set, so it holds the previous \
+                             frame's value",
+                            get.variable_name
+                        ),
+                    });
+                }
+            }
+            Movesampler1D::VariableSet(set) => {
+                vars.add_key(&set.set_variable_name);
+                written.insert(&set.set_variable_name);
+            }
+            _ => {}
+        }
+    }
+
+    for (i, shape) in cursor.iter_mut().enumerate() {
+        let seed = shape
+            .seed
+            .unwrap_or_else(|| derive_seed(unseeded, &[PathSegment::Index(i)]));
+        let children: Vec<(Vec<PathSegment>, *const Movesampler1D)> = shape
+            .children()
+            .into_iter()
+            .map(|(path, child)| (path, child as *const _))
+            .collect();
+        for ((path, expected), sampler) in children.into_iter().zip(shape.children_mut()) {
+            debug_assert!(std::ptr::eq(expected, sampler), "children out of order");
+            link_sampler(sampler, derive_seed(seed, &path), vars);
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linear_samplers::SamplerData;
+
+    #[test]
+    fn slots_and_read_before_write() {
+        let mut cursor: Vec<Shape> = serde_jsonrc::from_str(
+            r#"[
+  { "enabled": { "variable_name": "late" }, "movement": [], "shape": { "type": "NoDraw" } },
+  {
+    "enabled": {
+      "expressions": [
+        { "set_variable_name": "early", "value": 2.0 },
+        { "set_variable_name": "late", "value": { "variable_name": "early" } }
+      ]
+    },
+    "movement": [],
+    "shape": { "type": "NoDraw" }
+  }
+]"#,
+        )
+        .unwrap();
+
+        let mut data = SamplerData::new();
+        let problems = link(&mut cursor, &mut data.vars, 0);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].path.to_string(), "[0].enabled");
+        assert_eq!(data.vars.snapshot(), [("late", 0.0), ("early", 0.0)]);
+
+        for shape in cursor.iter_mut() {
+            shape.enabled.sample(&mut data);
+        }
+        assert_eq!(data.vars.snapshot(), [("late", 2.0), ("early", 2.0)]);
+
+        // Linking again, as a reload does, keeps the slots and their values.
+        link(&mut cursor, &mut data.vars, 0);
+        assert_eq!(data.vars.snapshot(), [("late", 2.0), ("early", 2.0)]);
+    }
+
+    #[test]
+    fn example_cursors_set_before_reading() {
+        for name in ["myCursor", "spinningDots"] {
+            let file = std::fs::File::open(format!("../cursors/{}.jsonc", name)).unwrap();
+            let mut cursor: Vec<Shape> = serde_jsonrc::from_reader(file).unwrap();
+            assert_eq!(link(&mut cursor, &mut DataHolder::new(), 0), [], "{}", name);
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! Reads the scroll wheel straight from the kernel's event devices, since device_query only
+//! reports buttons and position. Needs read access to `/dev/input/event*`, which usually means
+//! being in the `input` group.
+
+use std::{
+    fs::{self, File},
+    io::{self, Read},
+    path::Path,
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+const EV_REL: u16 = 0x02;
+const REL_WHEEL: u16 = 0x08;
+
+/// `struct input_event` is a `struct timeval` followed by a u16 type, a u16 code and an i32 value.
+const EVENT_SIZE: usize = 2 * std::mem::size_of::<usize>() + 8;
+
+/// Whether the device behind `/dev/input/<name>` has a vertical wheel, from the bitmask of
+/// relative axes in sysfs. The mask is written as hex words, lowest last.
+fn has_wheel(name: &str) -> bool {
+    let path = format!("/sys/class/input/{}/device/capabilities/rel", name);
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|mask| {
+            let lowest = mask.split_whitespace().last()?;
+            u64::from_str_radix(lowest, 16).ok()
+        })
+        .is_some_and(|mask| mask & (1 << REL_WHEEL) != 0)
+}
+
+fn read_events(mut device: File, total: Arc<AtomicI32>) {
+    let mut event = [0; EVENT_SIZE];
+    while device.read_exact(&mut event).is_ok() {
+        let kind = u16::from_ne_bytes([event[EVENT_SIZE - 8], event[EVENT_SIZE - 7]]);
+        let code = u16::from_ne_bytes([event[EVENT_SIZE - 6], event[EVENT_SIZE - 5]]);
+        if kind == EV_REL && code == REL_WHEEL {
+            let value = i32::from_ne_bytes(event[EVENT_SIZE - 4..].try_into().unwrap());
+            total.fetch_add(value, Ordering::Relaxed);
+        }
+    }
+}
+
+/// The total of every wheel notch scrolled since it was opened, up positive, summed over all
+/// mice. Each device is read on its own thread, which runs until the device goes away.
+pub struct LinuxScroll {
+    total: Arc<AtomicI32>,
+}
+
+impl LinuxScroll {
+    /// Fails if there is no wheel this user can read.
+    pub fn open() -> io::Result<Self> {
+        let total = Arc::new(AtomicI32::new(0));
+        let mut opened = 0;
+        for entry in fs::read_dir(Path::new("/dev/input"))? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !name.starts_with("event") || !has_wheel(&name) {
+                continue;
+            }
+            if let Ok(device) = File::open(entry.path()) {
+                let total = total.clone();
+                thread::spawn(move || read_events(device, total));
+                opened += 1;
+            }
+        }
+
+        if opened == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "no readable scroll wheel in /dev/input",
+            ));
+        }
+        Ok(Self { total })
+    }
+
+    pub fn total(&self) -> i32 {
+        self.total.load(Ordering::Relaxed)
+    }
+}
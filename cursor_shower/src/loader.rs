@@ -0,0 +1,307 @@
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::{
+    diagnostics::{diagnose, locate, JsonPath},
+    gen_shape::Shape,
+    random_samplers::entropy,
+    validate::validate,
+};
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(PathBuf, std::io::Error),
+    /// The file is not valid JSON, or serde rejected it for a reason the schema does not explain.
+    Parse(PathBuf, serde_jsonrc::Error),
+    /// The file is valid JSON, but not a valid cursor.
+    Invalid {
+        file: PathBuf,
+        line: usize,
+        column: usize,
+        path: JsonPath,
+        message: String,
+    },
+    /// The cursor loaded, but `validate` found problems that would show up once it runs. Each one
+    /// is an `Invalid` error. Only returned by `check_cursor` and `load_checked_cursor`.
+    Problems(Vec<LoadError>),
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Io(path, e) => write!(f, "could not read {}: {}", path.display(), e),
+            LoadError::Parse(path, e) => {
+                write!(f, "{}:{}:{}: {}", path.display(), e.line(), e.column(), e)
+            }
+            LoadError::Invalid {
+                file,
+                line,
+                column,
+                path,
+                message,
+            } => write!(
+                f,
+                "{}:{}:{}: at {}: {}",
+                file.display(),
+                line,
+                column,
+                path,
+                message
+            ),
+            LoadError::Problems(problems) => {
+                for (i, problem) in problems.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "{}", problem)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl LoadError {
+    fn with_file(self, file: &Path) -> Self {
+        match self {
+            LoadError::Io(_, e) => LoadError::Io(file.to_path_buf(), e),
+            LoadError::Parse(_, e) => LoadError::Parse(file.to_path_buf(), e),
+            LoadError::Invalid {
+                line,
+                column,
+                path,
+                message,
+                ..
+            } => LoadError::Invalid {
+                file: file.to_path_buf(),
+                line,
+                column,
+                path,
+                message,
+            },
+            LoadError::Problems(problems) => {
+                LoadError::Problems(problems.into_iter().map(|p| p.with_file(file)).collect())
+            }
+        }
+    }
+}
+
+fn read_cursor(
+    path: &Path,
+    parse: fn(&str) -> Result<Vec<Shape>, LoadError>,
+) -> Result<Vec<Shape>, LoadError> {
+    let text = std::fs::read_to_string(path).map_err(|e| LoadError::Io(path.to_path_buf(), e))?;
+    parse(&text).map_err(|e| e.with_file(path))
+}
+
+/// Loads a cursor file. Problems `validate` would find are left for the caller to report.
+pub fn load_cursor(path: impl AsRef<Path>) -> Result<Vec<Shape>, LoadError> {
+    read_cursor(path.as_ref(), parse_cursor)
+}
+
+/// Loads a cursor file and fails if `validate` finds any problems.
+pub fn load_checked_cursor(path: impl AsRef<Path>) -> Result<Vec<Shape>, LoadError> {
+    read_cursor(path.as_ref(), check_cursor)
+}
+
+/// Parses and validates the text of a cursor file, with every problem located in the text.
+/// Errors carry an empty file path.
+pub fn check_cursor(text: &str) -> Result<Vec<Shape>, LoadError> {
+    let cursor = parse_cursor(text)?;
+    let problems = validate(&cursor);
+    if problems.is_empty() {
+        return Ok(cursor);
+    }
+    Err(LoadError::Problems(
+        problems
+            .into_iter()
+            .map(|problem| {
+                let (line, column) = locate(text, &problem.path).unwrap_or((1, 1));
+                LoadError::Invalid {
+                    file: PathBuf::new(),
+                    line,
+                    column,
+                    path: problem.path,
+                    message: problem.message,
+                }
+            })
+            .collect(),
+    ))
+}
+
+/// Parses the text of a cursor file. Errors carry an empty file path.
+pub fn parse_cursor(text: &str) -> Result<Vec<Shape>, LoadError> {
+    let error = match serde_jsonrc::from_str::<Vec<Shape>>(text) {
+        Ok(cursor) => return Ok(cursor),
+        Err(e) => e,
+    };
+    if !error.is_data() {
+        return Err(LoadError::Parse(PathBuf::new(), error));
+    }
+
+    let diagnostic = serde_jsonrc::from_str(text).ok().and_then(|v| diagnose(&v));
+    match diagnostic {
+        Some(diagnostic) => {
+            let (line, column) =
+                locate(text, &diagnostic.path).unwrap_or((error.line(), error.column()));
+            Err(LoadError::Invalid {
+                file: PathBuf::new(),
+                line,
+                column,
+                path: diagnostic.path,
+                message: diagnostic.message,
+            })
+        }
+        None => Err(LoadError::Parse(PathBuf::new(), error)),
+    }
+}
+
+/// Watches a cursor file so it can be swapped in while the overlay is running.
+pub struct CursorWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    seed: u64,
+}
+
+impl CursorWatcher {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let modified = Self::modified_time(&path);
+        Self {
+            path,
+            modified,
+            seed: entropy(),
+        }
+    }
+
+    /// The seed to link every version of the cursor with, so shapes without their own `seed`
+    /// keep their random values across reloads.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn modified_time(path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+
+    /// Reloads the cursor if the file changed since the last call. Files that vanish for a moment,
+    /// as they do while some editors save, are waited out.
+    pub fn poll(&mut self) -> Option<Result<Vec<Shape>, LoadError>> {
+        let modified = Self::modified_time(&self.path)?;
+        if self.modified == Some(modified) {
+            return None;
+        }
+        self.modified = Some(modified);
+        Some(load_cursor(&self.path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, time::Duration};
+
+    use super::*;
+
+    /// A file in the temp dir that is removed again when the test ends, even if it fails.
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> Self {
+            let file = format!("cursor_{}_{}.jsonc", std::process::id(), name);
+            Self(std::env::temp_dir().join(file))
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn watcher_reloads_on_change() {
+        let file = TempFile::new("watcher_reloads_on_change");
+        let path = &file.0;
+        let write = |contents: &str, age: u64| {
+            std::fs::write(path, contents).unwrap();
+            File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(age))
+                .unwrap();
+        };
+
+        write("[]", 1);
+        let mut watcher = CursorWatcher::new(path);
+        assert!(watcher.poll().is_none());
+
+        write("[{", 2);
+        assert!(matches!(watcher.poll(), Some(Err(LoadError::Parse(..)))));
+        assert!(watcher.poll().is_none());
+
+        write(
+            r#"[{ "enabled": 1.0, "movement": [], "shape": { "type": "NoDraw" } }]"#,
+            3,
+        );
+        assert_eq!(watcher.poll().unwrap().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn readable_errors() {
+        let error = parse_cursor(
+            r#"[
+  {
+    "enabled": { "variable_nme": "gameMode" },
+    "movement": [],
+    "shape": { "type": "NoDraw" }
+  }
+]"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            ":3:16: at [0].enabled: does not match any Movesampler1D variant; closest is \
+             `VariableGet`, missing fields `variable_name`, unknown fields `variable_nme`"
+        );
+
+        let error = parse_cursor(
+            r#"[{ "enabled": { "type": "Time", "sped": 1.0 }, "movement": [], "shape": { "type": "NoDraw" } }]"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            ":1:15: at [0].enabled: missing fields `speed`"
+        );
+
+        let error = parse_cursor(
+            r#"[{ "enabled": { "keys": ["B", "Esc"] }, "movement": [], "shape": { "type": "NoDraw" } }]"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            ":1:31: at [0].enabled.keys[1]: unknown value `Esc`, did you mean `Escape`?"
+        );
+
+        let text = r#"[
+  { "enabled": { "type": "KeyChord", "keys": [] }, "movement": [], "shape": { "type": "NoDraw" } },
+  { "enabled": { "variable_name": "mode" }, "movement": [], "shape": { "type": "NoDraw" } }
+]"#;
+        // Problems only `validate` finds do not stop a cursor from loading.
+        assert_eq!(parse_cursor(text).unwrap().len(), 2);
+        let error = check_cursor(text).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            ":2:16: at [0].enabled: `KeyChord` has no keys\n\
+             :3:16: at [1].enabled: variable `mode` is never set by a VariableSet"
+        );
+
+        let error = parse_cursor("[{ \"enabled\": 1.0,, }]").unwrap_err();
+        assert!(matches!(error, LoadError::Parse(..)));
+        assert!(error.to_string().starts_with(":1:"));
+    }
+}
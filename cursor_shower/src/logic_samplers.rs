@@ -0,0 +1,292 @@
+//! Comparisons and boolean logic. Like `Switch` and `Shape.enabled`, a value counts as true when
+//! it is at least 1.0, and these samplers return 1.0 for true and 0.0 for false.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::linear_samplers::{child, child_list, Child, Movesampler1D, Sampler1D, SamplerData};
+
+pub fn is_true(value: f32) -> bool {
+    value >= 1.0
+}
+
+/// Samples every value, counting how many are true.
+fn count_true(values: &mut [Movesampler1D], data: &mut SamplerData) -> usize {
+    values
+        .iter_mut()
+        .map(|s| s.sample(data))
+        .filter(|v| is_true(*v))
+        .count()
+}
+
+pub fn from_bool(value: bool) -> f32 {
+    if value {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Greater {
+    pub left: Box<Movesampler1D>,
+    pub right: Box<Movesampler1D>,
+}
+
+impl Sampler1D for Greater {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        from_bool(self.left.sample(data) > self.right.sample(data))
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("left", &self.left), child("right", &self.right)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.left, &mut self.right]
+    }
+}
+
+impl From<Greater> for Movesampler1D {
+    fn from(g: Greater) -> Self {
+        Movesampler1D::Greater(g)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Less {
+    pub left: Box<Movesampler1D>,
+    pub right: Box<Movesampler1D>,
+}
+
+impl Sampler1D for Less {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        from_bool(self.left.sample(data) < self.right.sample(data))
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("left", &self.left), child("right", &self.right)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.left, &mut self.right]
+    }
+}
+
+impl From<Less> for Movesampler1D {
+    fn from(l: Less) -> Self {
+        Movesampler1D::Less(l)
+    }
+}
+
+fn default_epsilon() -> f32 {
+    1e-4
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Equal {
+    pub left: Box<Movesampler1D>,
+    pub right: Box<Movesampler1D>,
+    /// How far apart the two sides may be and still count as equal.
+    #[serde(default = "default_epsilon")]
+    pub epsilon: f32,
+}
+
+impl Sampler1D for Equal {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        from_bool((self.left.sample(data) - self.right.sample(data)).abs() <= self.epsilon)
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("left", &self.left), child("right", &self.right)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.left, &mut self.right]
+    }
+}
+
+impl From<Equal> for Movesampler1D {
+    fn from(e: Equal) -> Self {
+        Movesampler1D::Equal(e)
+    }
+}
+
+/// True when every value is. All values are sampled, so stateful samplers keep running.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct And {
+    pub values: Vec<Movesampler1D>,
+}
+
+impl Sampler1D for And {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        from_bool(count_true(&mut self.values, data) == self.values.len())
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        child_list("values", &self.values)
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        self.values.iter_mut().collect()
+    }
+}
+
+impl From<And> for Movesampler1D {
+    fn from(a: And) -> Self {
+        Movesampler1D::And(a)
+    }
+}
+
+/// True when any value is. All values are sampled, so stateful samplers keep running.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Or {
+    pub values: Vec<Movesampler1D>,
+}
+
+impl Sampler1D for Or {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        from_bool(count_true(&mut self.values, data) > 0)
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        child_list("values", &self.values)
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        self.values.iter_mut().collect()
+    }
+}
+
+impl From<Or> for Movesampler1D {
+    fn from(o: Or) -> Self {
+        Movesampler1D::Or(o)
+    }
+}
+
+/// True when an odd number of values are, which for two values means exactly one of them.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Xor {
+    pub values: Vec<Movesampler1D>,
+}
+
+impl Sampler1D for Xor {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        from_bool(count_true(&mut self.values, data) % 2 == 1)
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        child_list("values", &self.values)
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        self.values.iter_mut().collect()
+    }
+}
+
+impl From<Xor> for Movesampler1D {
+    fn from(x: Xor) -> Self {
+        Movesampler1D::Xor(x)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Not {
+    pub value: Box<Movesampler1D>,
+}
+
+impl Sampler1D for Not {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        from_bool(!is_true(self.value.sample(data)))
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("value", &self.value)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.value]
+    }
+}
+
+impl From<Not> for Movesampler1D {
+    fn from(n: Not) -> Self {
+        Movesampler1D::Not(n)
+    }
+}
+
+/// Picks `then` or `else` by `condition`. Only the chosen branch is sampled, so stateful samplers
+/// in the other one are paused.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct If {
+    pub condition: Box<Movesampler1D>,
+    pub then: Box<Movesampler1D>,
+    #[serde(rename = "else")]
+    pub otherwise: Box<Movesampler1D>,
+}
+
+impl Sampler1D for If {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        if is_true(self.condition.sample(data)) {
+            self.then.sample(data)
+        } else {
+            self.otherwise.sample(data)
+        }
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![
+            child("condition", &self.condition),
+            child("then", &self.then),
+            child("else", &self.otherwise),
+        ]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.condition, &mut self.then, &mut self.otherwise]
+    }
+}
+
+impl From<If> for Movesampler1D {
+    fn from(i: If) -> Self {
+        Movesampler1D::If(i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bloom_only_in_game_mode_and_not_scoped() {
+        // Holding shift scopes in; clicks only count while the If picks the counter.
+        let mut sampler: Movesampler1D = serde_jsonrc::from_str(
+            r#"{
+  "type": "If",
+  "condition": {
+    "type": "And",
+    "values": [
+      { "type": "Equal", "left": 1.0, "right": 1.00001 },
+      { "type": "Not", "value": { "keys": ["LShift"] } },
+      { "type": "Xor", "values": [1.0, { "type": "Less", "left": 2.0, "right": 1.0 }] }
+    ]
+  },
+  "then": { "mouse_click_counter_button": 1 },
+  "else": -1.0
+}"#,
+        )
+        .unwrap();
+        let mut data = SamplerData::new();
+        let mut step = |buttons: Vec<bool>, keys: Vec<device_query::Keycode>| {
+            data.input.buttons = buttons;
+            data.input.keys = keys;
+            sampler.sample(&mut data)
+        };
+        let shift = device_query::Keycode::LShift;
+        assert_eq!(step(vec![false, true], vec![]), 1.0);
+        assert_eq!(step(vec![false, false], vec![]), 1.0);
+        assert_eq!(step(vec![false, true], vec![shift]), -1.0);
+        assert_eq!(step(vec![false, false], vec![]), 1.0);
+        assert_eq!(step(vec![false, true], vec![]), 2.0);
+    }
+}
@@ -1,21 +1,179 @@
 #![windows_subsystem = "windows"]
-pub mod circle;
-pub mod gen_shape;
-pub mod hash_string;
-pub mod linear_samplers;
-pub mod movement;
-pub mod serde_keycode_serialize;
-pub mod variable_holder;
+
+use std::str::FromStr;
 
 use raylib::prelude::*;
 
-use crate::{
-    gen_shape::{Drawable, Shape},
-    linear_samplers::{Sampler1D, SamplerData},
+use cursor::{
+    gen_shape::Shape,
+    headless::evaluate_frame,
+    input::{DeviceQueryInput, InputSnapshot, RawInput},
+    linear_samplers::SamplerData,
+    link::link,
+    loader::{load_checked_cursor, load_cursor, CursorWatcher},
+    serde_keycode_serialize::parse_keycode,
+    software_render::render_cursor,
+    validate::validate,
     variable_holder::DataHolder,
 };
 
+const RENDER_USAGE: &str = "usage: cursor render <cursor.jsonc> <out.png> [--time SECONDS] \
+[--size WIDTHxHEIGHT] [--mouse X,Y] [--buttons 1,2,...] [--keys B,Escape,...]";
+
+const CONVERT_USAGE: &str = "usage: cursor convert <cursor.jsonc> [out.jsonc]";
+
+/// The overlay is a windows subsystem program so that double clicking it doesn't open a console,
+/// which also leaves it without anywhere to print. When started from a terminal, print there
+/// instead, so subcommands and reload errors are still visible.
+#[cfg(windows)]
+fn attach_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    // Fails when there is no parent console, which just means there is nowhere to print.
+    unsafe { AttachConsole(ATTACH_PARENT_PROCESS) };
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
 fn main() {
+    attach_console();
+    let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(String::as_str) {
+        Some("render") => {
+            if let Err(e) = render(&args[2..]) {
+                eprintln!("{}\n{}", e, RENDER_USAGE);
+                std::process::exit(1);
+            }
+        }
+        Some("convert") => {
+            if let Err(e) = convert(&args[2..]) {
+                eprintln!("{}\n{}", e, CONVERT_USAGE);
+                std::process::exit(1);
+            }
+        }
+        Some("validate") => match args.get(2) {
+            Some(cursor_path) => match load_checked_cursor(cursor_path) {
+                Ok(mut cursor) => {
+                    for warning in link(&mut cursor, &mut DataHolder::new(), 0) {
+                        println!("{}: warning: {}", cursor_path, warning);
+                    }
+                    println!("{}: ok", cursor_path);
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            },
+            None => {
+                eprintln!("usage: cursor validate <cursor.jsonc>");
+                std::process::exit(1);
+            }
+        },
+        path => overlay(path.unwrap_or("cursor.jsonc")),
+    }
+}
+
+fn parse_list<T: FromStr>(list: &str) -> Result<Vec<T>, String> {
+    list.split(',')
+        .map(|item| {
+            item.trim()
+                .parse()
+                .map_err(|_| format!("invalid value `{}`", item))
+        })
+        .collect()
+}
+
+/// Renders a still image of a cursor, without opening a window.
+fn render(args: &[String]) -> Result<(), String> {
+    let (cursor_path, out_path) = match args {
+        [cursor_path, out_path, ..] => (cursor_path, out_path),
+        _ => return Err("missing cursor or output path".to_string()),
+    };
+
+    let mut time = 1.0;
+    let mut size = (200, 200);
+    let mut input = RawInput::default();
+    for option in args[2..].chunks(2) {
+        let value = option
+            .get(1)
+            .ok_or_else(|| format!("missing value for `{}`", option[0]))?;
+        match option[0].as_str() {
+            "--time" => time = parse_list::<f32>(value)?[0],
+            "--size" => match parse_list::<u32>(&value.replace('x', ","))?[..] {
+                [w, h] => size = (w, h),
+                _ => return Err(format!("invalid size `{}`", value)),
+            },
+            "--mouse" => match parse_list::<i32>(value)?[..] {
+                [x, y] => input.position = (x, y),
+                _ => return Err(format!("invalid mouse position `{}`", value)),
+            },
+            "--buttons" => {
+                for button in parse_list::<usize>(value)? {
+                    if button >= input.buttons.len() {
+                        return Err(format!("invalid mouse button `{}`", button));
+                    }
+                    input.buttons[button] = true;
+                }
+            }
+            "--keys" => {
+                input.keys = value
+                    .split(',')
+                    .map(|k| parse_keycode(k.trim()).ok_or_else(|| format!("invalid key `{}`", k)))
+                    .collect::<Result<_, _>>()?;
+            }
+            other => return Err(format!("unknown option `{}`", other)),
+        }
+    }
+
+    let mut cursor = load_cursor(cursor_path).map_err(|e| e.to_string())?;
+    render_cursor(&mut cursor, time, &mut input, size)
+        .write_png(out_path)
+        .map_err(|e| format!("could not write {}: {}", out_path, e))
+}
+
+/// Rewrites a cursor with every sampler in the tagged form. Comments are not kept.
+fn convert(args: &[String]) -> Result<(), String> {
+    let (cursor_path, out_path) = match args {
+        [cursor_path] => (cursor_path, None),
+        [cursor_path, out_path] => (cursor_path, Some(out_path)),
+        _ => return Err("expected a cursor path and an optional output path".to_string()),
+    };
+
+    let cursor = load_cursor(cursor_path).map_err(|e| e.to_string())?;
+    let text = serde_jsonrc::to_string_pretty(&cursor).map_err(|e| e.to_string())?;
+    match out_path {
+        Some(out_path) => std::fs::write(out_path, text + "\n")
+            .map_err(|e| format!("could not write {}: {}", out_path, e)),
+        None => {
+            println!("{}", text);
+            Ok(())
+        }
+    }
+}
+
+/// Links a freshly loaded cursor against `data`, printing what `validate` and `link` find as
+/// warnings rather than refusing to run it.
+fn prepare(cursor_path: &str, cursor: &mut [Shape], data: &mut SamplerData, seed: u64) {
+    let problems = validate(cursor);
+    for warning in problems
+        .into_iter()
+        .chain(link(cursor, &mut data.vars, seed))
+    {
+        eprintln!("{}: warning: {}", cursor_path, warning);
+    }
+}
+
+fn overlay(cursor_path: &str) {
+    let mut cursor = load_cursor(cursor_path).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+    let mut watcher = CursorWatcher::new(cursor_path);
+
     let s = 100;
 
     let (mut rl, thread) = raylib::init()
@@ -44,36 +202,34 @@ fn main() {
 
     // rl.set_target_fps(5);
 
-    let mut cursor: Vec<Shape> = serde_jsonrc::from_reader(
-        std::fs::File::open(
-            std::env::args()
-                .skip(1)
-                .next()
-                .unwrap_or("cursor.jsonc".to_string()),
-        )
-        .unwrap(),
-    )
-    .unwrap();
-
     println!("serialized = {}", serde_jsonrc::to_string(&cursor).unwrap());
-    // let device_state = DeviceState::new();
+    let mut input = DeviceQueryInput::new();
+    if !input.reads_scroll() {
+        eprintln!(
+            "note: cannot read the scroll wheel, so scrolling reads as 0 \
+             (on Linux, add yourself to the `input` group)"
+        );
+    }
 
-    let mut data = SamplerData {
-        t: 0.0,
-        vars: DataHolder::new(),
-    };
+    let mut data = SamplerData::new();
+    prepare(cursor_path, &mut cursor, &mut data, watcher.seed());
 
     while !rl.window_should_close() {
         let mut d = rl.begin_drawing(&thread);
 
-        // rl.get_mouse_position()
-        // println!("{:?}", MouseCursor::pos());
-
-        // let mouse: MouseState = device_state.get_mouse();
-        // println!("Current Mouse Coordinates: {:?}", mouse.coords);
-        // println!("Current Mouse Clicks: {:?}", mouse.button_pressed);
-
         data.t += d.get_frame_time();
+        data.input = InputSnapshot::capture(&mut input, &data.input, data.t);
+
+        // Keep drawing the previous cursor if the edited one does not load.
+        match watcher.poll() {
+            Some(Ok(mut reloaded)) => {
+                println!("reloaded {}", cursor_path);
+                prepare(cursor_path, &mut reloaded, &mut data, watcher.seed());
+                cursor = reloaded;
+            }
+            Some(Err(e)) => eprintln!("{}", e),
+            None => {}
+        }
 
         d.clear_background(Color {
             r: 0,
@@ -82,11 +238,8 @@ fn main() {
             a: 0,
         });
 
-        for shape in &mut cursor {
-            if shape.enabled.sample(&mut data) >= 1.0 {
-                shape.draw(&mut d, &mut data, (sw >> 1, sh >> 1));
-            }
-            // shape.draw(&mut d, t, (pos.0 - 10, pos.1 - 10));
+        for command in evaluate_frame(&mut cursor, &mut data, (sw >> 1, sh >> 1)) {
+            command.draw(&mut d);
         }
     }
 }
@@ -1,13 +1,16 @@
 use std::f32::consts::PI;
 
-use device_query::{DeviceQuery, DeviceState};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::linear_samplers::{Movesampler1D, Sampler1D, SamplerData};
+use crate::linear_samplers::{child, Child, Movesampler1D, Sampler1D, SamplerData};
 
 pub trait Sampler2D {
     fn sample(&mut self, data: &mut SamplerData) -> (f32, f32);
+
+    fn children(&self) -> Vec<Child<'_>>;
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D>;
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -26,6 +29,22 @@ impl Sampler2D for Movesampler2D {
             Movesampler2D::Mouse(m) => m.sample(data),
         }
     }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        match self {
+            Movesampler2D::Orbit(o) => o.children(),
+            Movesampler2D::Offset(o) => o.children(),
+            Movesampler2D::Mouse(m) => m.children(),
+        }
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        match self {
+            Movesampler2D::Orbit(o) => o.children_mut(),
+            Movesampler2D::Offset(o) => o.children_mut(),
+            Movesampler2D::Mouse(m) => m.children_mut(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -40,18 +59,41 @@ pub struct Orbit {
 
 impl Sampler2D for Orbit {
     fn sample(&mut self, data: &mut SamplerData) -> (f32, f32) {
-        let pre_x = (self.speed.sample(data) * data.t).cos() * self.eccentricity.sample(data);
-        let pre_y = (self.speed.sample(data) * data.t).sin();
+        // Each child is sampled once, as stateful samplers step on every call.
+        let speed = self.speed.sample(data);
+        let pre_x = (speed * data.t).cos() * self.eccentricity.sample(data);
+        let pre_y = (speed * data.t).sin();
 
         let angle = (self.angle_top.sample(data) / self.angle_bottom.sample(data)) * 2.0_f32 * PI;
 
         let cos_v = angle.cos();
         let sin_v = angle.sin();
 
-        let x = (pre_x * cos_v - pre_y * sin_v) * self.radius.sample(data);
-        let y = (pre_y * cos_v + pre_x * sin_v) * self.radius.sample(data);
+        let radius = self.radius.sample(data);
+        let x = (pre_x * cos_v - pre_y * sin_v) * radius;
+        let y = (pre_y * cos_v + pre_x * sin_v) * radius;
+
+        (x, y)
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![
+            child("speed", &self.speed),
+            child("eccentricity", &self.eccentricity),
+            child("angle_top", &self.angle_top),
+            child("angle_bottom", &self.angle_bottom),
+            child("radius", &self.radius),
+        ]
+    }
 
-        return (x, y);
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![
+            &mut self.speed,
+            &mut self.eccentricity,
+            &mut self.angle_top,
+            &mut self.angle_bottom,
+            &mut self.radius,
+        ]
     }
 }
 
@@ -71,6 +113,14 @@ impl Sampler2D for Offset {
     fn sample(&mut self, data: &mut SamplerData) -> (f32, f32) {
         (self.x.sample(data), self.y.sample(data))
     }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("x", &self.x), child("y", &self.y)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.x, &mut self.y]
+    }
 }
 
 impl From<Offset> for Movesampler2D {
@@ -88,12 +138,119 @@ pub struct Mouse {
 
 impl Sampler2D for Mouse {
     fn sample(&mut self, data: &mut SamplerData) -> (f32, f32) {
-        let device_state = DeviceState::new();
-        let mouse = device_state.get_mouse();
+        let (x, y) = data.input.position;
+        let scale_center_x = self.scale_center_x.sample(data);
+        let scale_center_y = self.scale_center_y.sample(data);
+        let scale = self.scale.sample(data);
+        (
+            (x as f32 - scale_center_x) * scale,
+            (y as f32 - scale_center_y) * scale,
+        )
+    }
 
-        let (x, y) = mouse.coords;
-        let x = (x as f32 - self.scale_center_x.sample(data)) * self.scale.sample(data);
-        let y = (y as f32 - self.scale_center_y.sample(data)) * self.scale.sample(data);
-        (x, y)
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![
+            child("scale_center_x", &self.scale_center_x),
+            child("scale_center_y", &self.scale_center_y),
+            child("scale", &self.scale),
+        ]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![
+            &mut self.scale_center_x,
+            &mut self.scale_center_y,
+            &mut self.scale,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gen_shape::{DrawCommand, Drawable, Shape},
+        input::InputSnapshot,
+        link::link,
+        scripted_input::{InputEvent, ScriptedInput},
+    };
+
+    #[test]
+    fn orbit_steps_its_samplers_once_per_frame() {
+        let spring = r#"{ "type": "Spring", "target": { "keys": ["Space"] } }"#;
+        let mut orbit: Movesampler2D = serde_jsonrc::from_str(&format!(
+            r#"{{
+  "type": "Orbit",
+  "speed": 0.0,
+  "eccentricity": 1.0,
+  "angle_top": 0.0,
+  "angle_bottom": 1.0,
+  "radius": {}
+}}"#,
+            spring
+        ))
+        .unwrap();
+        let mut alone: Movesampler1D = serde_jsonrc::from_str(spring).unwrap();
+
+        let mut data = SamplerData::new();
+        data.input.delta_time = 1.0 / 60.0;
+        for frame in 0..30 {
+            data.input.keys = if frame > 0 {
+                vec![device_query::Keycode::Space]
+            } else {
+                Vec::new()
+            };
+            let (x, _) = orbit.sample(&mut data);
+            assert_eq!(x, alone.sample(&mut data), "frame {}", frame);
+        }
+    }
+
+    /// Runs an example cursor for two seconds of clicking and key presses. With `extra_sample`,
+    /// every movement is sampled once more before each frame, as `Orbit` and `Mouse` used to
+    /// sample their children twice.
+    fn run_example(name: &str, extra_sample: bool) -> Vec<Vec<DrawCommand>> {
+        let file = std::fs::File::open(format!("../cursors/{}.jsonc", name)).unwrap();
+        let mut cursor: Vec<Shape> = serde_jsonrc::from_reader(file).unwrap();
+        let mut input = ScriptedInput::default()
+            .at(0.1, InputEvent::KeyDown(device_query::Keycode::B))
+            .at(0.2, InputEvent::KeyUp(device_query::Keycode::B))
+            .at(0.5, InputEvent::ButtonDown(1))
+            .at(0.6, InputEvent::ButtonUp(1))
+            .at(1.0, InputEvent::KeyDown(device_query::Keycode::Escape))
+            .at(1.1, InputEvent::KeyUp(device_query::Keycode::Escape))
+            .at(1.5, InputEvent::ButtonDown(1))
+            .at(1.6, InputEvent::ButtonUp(1));
+        let mut data = SamplerData::new();
+        link(&mut cursor, &mut data.vars, 0);
+        (0..120)
+            .map(|_| {
+                data.t += 1.0 / 60.0;
+                data.input = InputSnapshot::capture(&mut input, &data.input, data.t);
+                let mut commands = Vec::new();
+                for shape in cursor.iter_mut() {
+                    if shape.enabled.sample(&mut data) >= 1.0 {
+                        if extra_sample {
+                            for movement in shape.movement.iter_mut() {
+                                movement.sample(&mut data);
+                            }
+                        }
+                        commands.push(shape.evaluate(&mut data, (0, 0)));
+                    }
+                }
+                commands
+            })
+            .collect()
+    }
+
+    #[test]
+    fn example_cursors_do_not_depend_on_sample_count() {
+        for name in ["myCursor", "spinningDots"] {
+            assert_eq!(
+                run_example(name, false),
+                run_example(name, true),
+                "{}",
+                name
+            );
+        }
     }
 }
@@ -0,0 +1,305 @@
+//! Random values and smooth noise. Each sampler gets its own seed from `link::link`, derived from
+//! the seed of its shape, so a shape with a fixed `seed` plays out the same way every run.
+
+use std::hash::{BuildHasher, Hasher};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    linear_samplers::{child, Child, Movesampler1D, Sampler1D, SamplerData, Time},
+    logic_samplers::is_true,
+};
+
+/// SplitMix64, which is small, fast, and good enough for moving crosshairs around.
+#[derive(Debug, Clone, Default)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A uniform value from 0 (inclusive) to 1 (exclusive).
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+/// A seed that is different every run, for shapes without a fixed one.
+pub fn entropy() -> u64 {
+    std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish()
+}
+
+fn zero() -> f32 {
+    0.0
+}
+
+fn one() -> f32 {
+    1.0
+}
+
+fn always() -> Box<Movesampler1D> {
+    Box::new(Movesampler1D::Constant(1.0))
+}
+
+/// A uniform random value between `min` and `max`. A new value is rolled on every frame
+/// `trigger` is true, which by default is every frame, and held otherwise.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Random {
+    #[serde(default = "zero")]
+    pub min: f32,
+    #[serde(default = "one")]
+    pub max: f32,
+    #[serde(default = "always")]
+    pub trigger: Box<Movesampler1D>,
+    #[serde(skip)]
+    rng: Rng,
+    #[serde(skip)]
+    value: Option<f32>,
+}
+
+impl Sampler1D for Random {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let trigger = is_true(self.trigger.sample(data));
+        match self.value {
+            Some(value) if !trigger => value,
+            _ => {
+                let value = self.min + (self.max - self.min) * self.rng.next_f32();
+                self.value = Some(value);
+                value
+            }
+        }
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("trigger", &self.trigger)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.trigger]
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+        self.value = None;
+    }
+}
+
+impl From<Random> for Movesampler1D {
+    fn from(r: Random) -> Self {
+        Movesampler1D::Random(r)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum NoiseKind {
+    /// Random values at whole numbers, smoothly blended. Blobby.
+    Value,
+    /// Random slopes at whole numbers. Smoother, and zero at every whole number.
+    Perlin,
+    /// Ken Perlin's later noise. Like Perlin, with less regular looking peaks.
+    Simplex,
+}
+
+fn lattice_hash(seed: u64, i: i64) -> u64 {
+    Rng::new(seed ^ (i as u64).wrapping_mul(0xD6E8_FEB8_6659_FD93)).next_u64()
+}
+
+/// A value from -1 to 1 for the lattice point `i`.
+fn lattice_value(seed: u64, i: i64) -> f32 {
+    Rng::new(lattice_hash(seed, i)).next_f32() * 2.0 - 1.0
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+impl NoiseKind {
+    /// Noise at `x`, from about -1 to 1.
+    pub fn at(self, seed: u64, x: f32) -> f32 {
+        let i = x.floor() as i64;
+        let f = x - x.floor();
+        match self {
+            NoiseKind::Value => {
+                let (a, b) = (lattice_value(seed, i), lattice_value(seed, i + 1));
+                a + (b - a) * fade(f)
+            }
+            NoiseKind::Perlin => {
+                let a = lattice_value(seed, i) * f;
+                let b = lattice_value(seed, i + 1) * (f - 1.0);
+                // A slope of at most 1 over half a unit peaks at 0.5, so double it.
+                2.0 * (a + (b - a) * fade(f))
+            }
+            NoiseKind::Simplex => {
+                let corner = |i: i64, d: f32| {
+                    let t = 1.0 - d * d;
+                    let h = lattice_hash(seed, i);
+                    let gradient = (1 + (h & 7)) as f32 * if h & 8 == 0 { 1.0 } else { -1.0 };
+                    t * t * t * t * gradient * d
+                };
+                // Scale from Stefan Gustavson's reference implementation.
+                0.395 * (corner(i, f) + corner(i + 1, f - 1.0))
+            }
+        }
+    }
+}
+
+fn default_input() -> Box<Movesampler1D> {
+    Box::new(Movesampler1D::Time(Time { speed: 1.0 }))
+}
+
+/// Smooth noise from -`amplitude` to `amplitude` over `input` (seconds unless given), changing
+/// about `frequency` times per unit.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Noise1D {
+    pub kind: NoiseKind,
+    #[serde(default = "default_input")]
+    pub input: Box<Movesampler1D>,
+    #[serde(default = "one")]
+    pub frequency: f32,
+    #[serde(default = "one")]
+    pub amplitude: f32,
+    #[serde(skip)]
+    seed: u64,
+}
+
+impl Sampler1D for Noise1D {
+    fn sample(&mut self, data: &mut SamplerData) -> f32 {
+        let x = self.input.sample(data) * self.frequency;
+        self.amplitude * self.kind.at(self.seed, x)
+    }
+
+    fn children(&self) -> Vec<Child<'_>> {
+        vec![child("input", &self.input)]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Movesampler1D> {
+        vec![&mut self.input]
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+}
+
+impl From<Noise1D> for Movesampler1D {
+    fn from(n: Noise1D) -> Self {
+        Movesampler1D::Noise1D(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{gen_shape::Shape, link::link, movement::Sampler2D};
+
+    const CURSOR: &str = r#"[
+  {
+    "seed": 7,
+    "enabled": { "type": "Random", "min": 1.0, "max": 2.0, "trigger": { "keys": ["R"] } },
+    "movement": [
+      {
+        "type": "Offset",
+        "x": { "type": "Noise1D", "kind": "Perlin", "frequency": 3.0 },
+        "y": { "type": "Noise1D", "kind": "Simplex", "amplitude": 10.0 }
+      }
+    ],
+    "shape": { "type": "NoDraw" }
+  }
+]"#;
+
+    fn run(frames: usize) -> Vec<(f32, f32, f32)> {
+        let mut cursor: Vec<Shape> = serde_jsonrc::from_str(CURSOR).unwrap();
+        let mut data = SamplerData::new();
+        link(&mut cursor, &mut data.vars, 0);
+        let shape = &mut cursor[0];
+        let mut values = Vec::new();
+        for frame in 0..frames {
+            data.t = frame as f32 * 0.1;
+            data.input.keys = if frame == 2 {
+                vec![device_query::Keycode::R]
+            } else {
+                Vec::new()
+            };
+            let enabled = shape.enabled.sample(&mut data);
+            let (x, y) = shape.movement[0].sample(&mut data);
+            values.push((enabled, x, y));
+        }
+        values
+    }
+
+    #[test]
+    fn seeded_random_is_reproducible() {
+        let frames = run(4);
+        assert_eq!(frames, run(4));
+        assert!(frames
+            .iter()
+            .all(|(_, x, y)| x.abs() <= 1.0 && y.abs() <= 10.0));
+        let values: Vec<f32> = frames.iter().map(|f| f.0).collect();
+        assert!(values.iter().all(|v| (1.0..2.0).contains(v)));
+        // Rolled on the first frame, held, then re-rolled on the trigger.
+        assert_eq!(values[0], values[1]);
+        assert_ne!(values[1], values[2]);
+        assert_eq!(values[2], values[3]);
+    }
+
+    #[test]
+    fn noise_is_smooth_and_bounded() {
+        for kind in [NoiseKind::Value, NoiseKind::Perlin, NoiseKind::Simplex] {
+            let samples: Vec<f32> = (0..2000).map(|i| kind.at(3, i as f32 * 0.01)).collect();
+            assert!(samples.iter().all(|v| v.abs() <= 1.0), "{:?}", kind);
+            assert!(
+                samples.windows(2).all(|w| (w[0] - w[1]).abs() < 0.1),
+                "{:?} jumps",
+                kind
+            );
+            assert!(samples.iter().any(|v| v.abs() > 0.3), "{:?} is flat", kind);
+            assert_ne!(
+                kind.at(3, 0.5),
+                kind.at(4, 0.5),
+                "{:?} ignores the seed",
+                kind
+            );
+        }
+    }
+
+    /// The value of the `Random` at `x` in the first movement of the only shape in `cursor`.
+    fn offset_x(cursor: &str, unseeded: u64) -> f32 {
+        let mut cursor: Vec<Shape> = serde_jsonrc::from_str(cursor).unwrap();
+        let mut data = SamplerData::new();
+        link(&mut cursor, &mut data.vars, unseeded);
+        cursor[0].movement[0].sample(&mut data).0
+    }
+
+    #[test]
+    fn seeds_follow_paths() {
+        let cursor = |enabled: &str| {
+            format!(
+                r#"[{{
+  "enabled": {},
+  "movement": [{{ "type": "Offset", "x": {{ "type": "Random" }}, "y": 0.0 }}],
+  "shape": {{ "type": "NoDraw" }}
+}}]"#,
+                enabled
+            )
+        };
+        let plain = cursor("1.0");
+        // A reload links with the same seed, so unseeded shapes keep their values.
+        assert_eq!(offset_x(&plain, 5), offset_x(&plain, 5));
+        assert_ne!(offset_x(&plain, 5), offset_x(&plain, 6));
+        // Adding samplers before it in the same shape leaves its seed alone.
+        let busier = cursor(r#"{ "type": "Random", "trigger": { "type": "Random" } }"#);
+        assert_eq!(offset_x(&plain, 5), offset_x(&busier, 5));
+    }
+}
@@ -0,0 +1,53 @@
+use raylib::prelude::*;
+
+use crate::gen_shape::{DrawCommand, ResolvedShape};
+
+/// Something `DrawCommand`s can be drawn onto.
+pub trait RenderTarget {
+    fn circle_gradient(&mut self, center: (i32, i32), radius: f32, inner: Color, outer: Color);
+
+    /// Corner colors are given clockwise from the top left.
+    fn rect_gradient(&mut self, rect: Rectangle, tl: Color, tr: Color, br: Color, bl: Color);
+}
+
+impl RenderTarget for RaylibDrawHandle<'_> {
+    fn circle_gradient(&mut self, center: (i32, i32), radius: f32, inner: Color, outer: Color) {
+        self.draw_circle_gradient(center.0, center.1, radius, inner, outer);
+    }
+
+    fn rect_gradient(&mut self, rect: Rectangle, tl: Color, tr: Color, br: Color, bl: Color) {
+        self.draw_rectangle_gradient_ex(rect, tl, bl, br, tr);
+    }
+}
+
+impl DrawCommand {
+    pub fn draw(&self, target: &mut impl RenderTarget) {
+        match self.shape {
+            ResolvedShape::Circle {
+                radius,
+                inner_color,
+                outer_color,
+            } => target.circle_gradient(self.position, radius, inner_color, outer_color),
+            ResolvedShape::NoDraw => {}
+            ResolvedShape::Rect {
+                width,
+                height,
+                color_tl,
+                color_tr,
+                color_bl,
+                color_br,
+            } => target.rect_gradient(
+                Rectangle {
+                    x: self.position.0 as f32,
+                    y: self.position.1 as f32,
+                    width,
+                    height,
+                },
+                color_tl,
+                color_tr,
+                color_br,
+                color_bl,
+            ),
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use device_query::Keycode;
+
+use crate::input::{InputSource, RawInput};
+
+#[derive(Debug, Clone)]
+pub enum InputEvent {
+    ButtonDown(usize),
+    ButtonUp(usize),
+    KeyDown(Keycode),
+    KeyUp(Keycode),
+    MoveTo(i32, i32),
+    /// Scrolls the wheel by this many notches, up positive.
+    Scroll(i32),
+}
+
+/// Replays a timeline of input events, so cursors can be evaluated without any input devices.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptedInput {
+    events: Vec<(f32, InputEvent)>,
+    next_event: usize,
+    state: RawInput,
+}
+
+impl ScriptedInput {
+    pub fn new(mut events: Vec<(f32, InputEvent)>) -> Self {
+        events.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self {
+            events,
+            next_event: 0,
+            state: RawInput::default(),
+        }
+    }
+
+    /// Adds an event at `time`, for building scripts inline.
+    pub fn at(self, time: f32, event: InputEvent) -> Self {
+        let mut events = self.events;
+        events.push((time, event));
+        Self::new(events)
+    }
+
+    fn apply(&mut self, event: &InputEvent) {
+        match *event {
+            InputEvent::ButtonDown(button) => self.set_button(button, true),
+            InputEvent::ButtonUp(button) => self.set_button(button, false),
+            InputEvent::KeyDown(key) => {
+                if !self.state.keys.contains(&key) {
+                    self.state.keys.push(key);
+                }
+            }
+            InputEvent::KeyUp(key) => self.state.keys.retain(|k| *k != key),
+            InputEvent::MoveTo(x, y) => self.state.position = (x, y),
+            InputEvent::Scroll(notches) => self.state.scroll += notches,
+        }
+    }
+
+    fn set_button(&mut self, button: usize, pressed: bool) {
+        if button >= self.state.buttons.len() {
+            self.state.buttons.resize(button + 1, false);
+        }
+        self.state.buttons[button] = pressed;
+    }
+}
+
+impl InputSource for ScriptedInput {
+    fn poll(&mut self, time: f32) -> RawInput {
+        while let Some((at, event)) = self.events.get(self.next_event).cloned() {
+            if at > time {
+                break;
+            }
+            self.apply(&event);
+            self.next_event += 1;
+        }
+        self.state.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gen_shape::Shape,
+        input::InputSnapshot,
+        linear_samplers::{Sampler1D, SamplerData},
+        link::link,
+    };
+
+    fn run(cursor: &mut [Shape], input: &mut ScriptedInput, data: &mut SamplerData, until: f32) {
+        while data.t < until {
+            data.t += 1.0 / 60.0;
+            data.input = InputSnapshot::capture(input, &data.input, data.t);
+            for shape in cursor.iter_mut() {
+                shape.enabled.sample(data);
+            }
+        }
+    }
+
+    fn var(data: &SamplerData, name: &str) -> f32 {
+        data.vars.get(*data.vars.get_key(name).unwrap())
+    }
+
+    #[test]
+    fn scripted_spinning_dots() {
+        let mut cursor: Vec<Shape> = serde_jsonrc::from_reader(
+            std::fs::File::open("../cursors/spinningDots.jsonc").unwrap(),
+        )
+        .unwrap();
+        let mut input = ScriptedInput::default()
+            .at(0.5, InputEvent::KeyDown(Keycode::B))
+            .at(0.6, InputEvent::KeyUp(Keycode::B))
+            .at(1.0, InputEvent::KeyDown(Keycode::Escape))
+            .at(1.1, InputEvent::KeyUp(Keycode::Escape))
+            .at(2.0, InputEvent::ButtonDown(1))
+            .at(2.1, InputEvent::ButtonUp(1));
+        let mut data = SamplerData::new();
+        link(&mut cursor, &mut data.vars, 0);
+
+        run(&mut cursor, &mut input, &mut data, 0.55);
+        assert_eq!(var(&data, "buyMode"), 1.0);
+        assert_eq!(var(&data, "gameMode"), 0.0);
+
+        run(&mut cursor, &mut input, &mut data, 1.5);
+        assert_eq!(var(&data, "buyMode"), 0.0);
+        assert_eq!(var(&data, "gameMode"), 1.0);
+        assert_eq!(var(&data, "clickRadius"), 40.0);
+
+        // The left click pulls the dots in, then they spring back out.
+        run(&mut cursor, &mut input, &mut data, 2.2);
+        assert!(var(&data, "clickRadius") < 40.0);
+        run(&mut cursor, &mut input, &mut data, 3.0);
+        assert!((var(&data, "clickRadius") - 40.0).abs() < 0.1);
+    }
+}
@@ -1,8 +1,12 @@
 use device_query::Keycode;
-use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use serde::{de::IntoDeserializer, Deserialize, Deserializer, Serialize, Serializer};
 
-#[derive(Serialize, Deserialize, Debug)]
+// Use with `#[serde(with = "KeycodeDef")]` on a `Keycode` field, and `keycode_vec` on a list.
+/// A key, like `B`, `Escape`, `LShift` or `Numpad1`.
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(remote = "Keycode")]
+#[schemars(rename = "Keycode")]
 pub enum KeycodeDef {
     Key0,
     Key1,
@@ -78,6 +82,8 @@ pub enum KeycodeDef {
 
     // The following keys have not been tested on MacOS!
     // Numpad keys which have not been implemented: NumpadSeparator NumLock
+    // device_query 1.1.1 has no variants for them (or NumpadDecimal and NumpadEnter), so they
+    // cannot be added here until it is updated.
     Numpad0,
     Numpad1,
     Numpad2,
@@ -110,6 +116,34 @@ pub enum KeycodeDef {
 
 pub type KeycodeDefVec = Vec<KeycodeDef>;
 
+#[derive(Serialize, Deserialize)]
+struct KeycodeWrapper(#[serde(with = "KeycodeDef")] Keycode);
+
+/// The key named `name`, spelled as in a cursor file. Unlike `Keycode::from_str`, this knows
+/// every key a cursor can use, like `Key1` and `Numpad1`.
+pub fn parse_keycode(name: &str) -> Option<Keycode> {
+    let deserializer: serde::de::value::StrDeserializer<serde::de::value::Error> =
+        name.into_deserializer();
+    KeycodeDef::deserialize(deserializer).ok()
+}
+
+/// `#[serde(with = "keycode_vec")]` for a `Vec<Keycode>`, paired with
+/// `#[schemars(with = "KeycodeDefVec")]` for its schema.
+pub mod keycode_vec {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(keys: &[Keycode], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(keys.iter().map(|k| KeycodeWrapper(*k)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Keycode>, D::Error> {
+        let keys = Vec::<KeycodeWrapper>::deserialize(deserializer)?;
+        Ok(keys.into_iter().map(|k| k.0).collect())
+    }
+}
+
 // impl From<KeycodeDef> for Keycode {
 //     fn from(k: KeycodeDef) -> Self {
 //         match k {
@@ -212,3 +246,16 @@ pub type KeycodeDefVec = Vec<KeycodeDef>;
 //         }
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_key_name() {
+        assert_eq!(parse_keycode("Numpad1"), Some(Keycode::Numpad1));
+        assert_eq!(parse_keycode("Key0"), Some(Keycode::Key0));
+        assert_eq!(parse_keycode("Escape"), Some(Keycode::Escape));
+        assert_eq!(parse_keycode("Esc"), None);
+    }
+}
@@ -0,0 +1,211 @@
+use std::{fs::File, io::BufWriter, path::Path};
+
+use raylib::prelude::*;
+
+use crate::{
+    gen_shape::{DrawCommand, Shape},
+    headless::evaluate_frames,
+    input::InputSource,
+    render::RenderTarget,
+};
+
+/// Frame rate cursors are simulated at when rendering a still image.
+pub const RENDER_FPS: f32 = 60.0;
+
+/// An RGBA image drawn on the CPU, for rendering cursors without a GPU.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Canvas {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<Color>,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![Color::new(0, 0, 0, 0); (width * height) as usize],
+        }
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Color {
+        self.pixels[(y * self.width + x) as usize]
+    }
+
+    /// Composites `color` over the pixel at `(x, y)`, ignoring pixels off the canvas.
+    fn blend(&mut self, x: i32, y: i32, color: Color) {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return;
+        }
+        let index = (y as u32 * self.width + x as u32) as usize;
+        let dst = self.pixels[index];
+
+        let src_a = color.a as f32 / 255.0;
+        let dst_a = dst.a as f32 / 255.0;
+        let out_a = src_a + dst_a * (1.0 - src_a);
+        if out_a <= 0.0 {
+            self.pixels[index] = Color::new(0, 0, 0, 0);
+            return;
+        }
+
+        let channel = |s: u8, d: u8| {
+            ((s as f32 * src_a + d as f32 * dst_a * (1.0 - src_a)) / out_a).round() as u8
+        };
+        self.pixels[index] = Color::new(
+            channel(color.r, dst.r),
+            channel(color.g, dst.g),
+            channel(color.b, dst.b),
+            (out_a * 255.0).round() as u8,
+        );
+    }
+
+    pub fn to_rgba(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|c| [c.r, c.g, c.b, c.a])
+            .collect()
+    }
+
+    pub fn write_png(&self, path: impl AsRef<Path>) -> Result<(), png::EncodingError> {
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(file, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()?.write_image_data(&self.to_rgba())
+    }
+}
+
+fn lerp_color(a: Color, b: Color, t: f32) -> Color {
+    let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    Color::new(
+        channel(a.r, b.r),
+        channel(a.g, b.g),
+        channel(a.b, b.b),
+        channel(a.a, b.a),
+    )
+}
+
+/// The pixels from `from` up to `to` that are on a canvas `size` pixels across. Shape sizes come from
+/// samplers and can be huge or infinite, so this is worked out before converting to integers.
+fn clip(from: f32, to: f32, size: u32) -> std::ops::Range<i32> {
+    let clamp = |v: f32| v.clamp(0.0, size as f32) as i32;
+    clamp(from)..clamp(to)
+}
+
+impl RenderTarget for Canvas {
+    fn circle_gradient(&mut self, center: (i32, i32), radius: f32, inner: Color, outer: Color) {
+        let reach = radius.ceil();
+        let (cx, cy) = (center.0 as f32, center.1 as f32);
+        for y in clip(cy - reach, cy + reach + 1.0, self.height) {
+            for x in clip(cx - reach, cx + reach + 1.0, self.width) {
+                // Sample at the pixel center, like a rasterized triangle fan would.
+                let dx = x as f32 + 0.5 - cx;
+                let dy = y as f32 + 0.5 - cy;
+                let distance = (dx * dx + dy * dy).sqrt();
+                if distance <= radius {
+                    self.blend(x, y, lerp_color(inner, outer, distance / radius));
+                }
+            }
+        }
+    }
+
+    fn rect_gradient(&mut self, rect: Rectangle, tl: Color, tr: Color, br: Color, bl: Color) {
+        let xs = clip(rect.x.round(), (rect.x + rect.width).round(), self.width);
+        let ys = clip(rect.y.round(), (rect.y + rect.height).round(), self.height);
+        for y in ys {
+            let v = (y as f32 + 0.5 - rect.y) / rect.height;
+            let left = lerp_color(tl, bl, v);
+            let right = lerp_color(tr, br, v);
+            for x in xs.clone() {
+                let u = (x as f32 + 0.5 - rect.x) / rect.width;
+                self.blend(x, y, lerp_color(left, right, u));
+            }
+        }
+    }
+}
+
+/// Draws one frame of draw commands centered on a canvas of the given size.
+pub fn render_frame(commands: &[DrawCommand], size: (u32, u32)) -> Canvas {
+    let mut canvas = Canvas::new(size.0, size.1);
+    let center = ((size.0 / 2) as i32, (size.1 / 2) as i32);
+    for command in commands {
+        DrawCommand {
+            position: (command.position.0 + center.0, command.position.1 + center.1),
+            ..command.clone()
+        }
+        .draw(&mut canvas);
+    }
+    canvas
+}
+
+/// Simulates `cursor` up to `time` seconds with input from `input`, and draws the final frame.
+pub fn render_cursor(
+    cursor: &mut [Shape],
+    time: f32,
+    input: &mut impl InputSource,
+    size: (u32, u32),
+) -> Canvas {
+    let frames = ((time * RENDER_FPS).round() as usize).max(1);
+    let last = evaluate_frames(cursor, 1.0 / RENDER_FPS, frames, input)
+        .pop()
+        .unwrap_or_default();
+    render_frame(&last, size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::RawInput;
+
+    #[test]
+    fn gradients() {
+        let mut canvas = Canvas::new(20, 10);
+        let red = Color::new(255, 0, 0, 255);
+        let blue = Color::new(0, 0, 255, 255);
+
+        canvas.circle_gradient((5, 5), 4.0, red, Color::new(255, 0, 0, 0));
+        assert_eq!(canvas.pixel(5, 5).r, 255);
+        assert!(canvas.pixel(5, 5).a > 200);
+        assert_eq!(canvas.pixel(0, 0).a, 0);
+
+        canvas.rect_gradient(Rectangle::new(10.0, 0.0, 10.0, 10.0), red, blue, blue, red);
+        assert_eq!(canvas.pixel(10, 5).r, 242);
+        assert_eq!(canvas.pixel(19, 5).b, 242);
+        assert_eq!(canvas.pixel(9, 5).a, 0);
+
+        // Sizes from a division by zero or a runaway sampler are clipped to the canvas.
+        let mut canvas = Canvas::new(4, 4);
+        canvas.rect_gradient(
+            Rectangle::new(1.0, 1.0, f32::INFINITY, 1e30),
+            red,
+            red,
+            red,
+            red,
+        );
+        assert_eq!(canvas.pixel(3, 3), red);
+        assert_eq!(canvas.pixel(0, 0).a, 0);
+        canvas.circle_gradient((2, 2), 1e20, blue, blue);
+        assert_eq!(canvas.pixel(0, 0), blue);
+        canvas.circle_gradient((i32::MAX, 0), f32::NAN, red, red);
+    }
+
+    #[test]
+    fn render_spinning_dots() {
+        let load = || -> Vec<Shape> {
+            serde_jsonrc::from_reader(std::fs::File::open("../cursors/spinningDots.jsonc").unwrap())
+                .unwrap()
+        };
+
+        // spinningDots draws nothing until B or Escape picks a mode.
+        let canvas = render_cursor(&mut load(), 0.5, &mut RawInput::default(), (64, 64));
+        assert!(canvas.pixels.iter().all(|p| p.a == 0));
+
+        let mut escape = RawInput {
+            keys: vec![device_query::Keycode::Escape],
+            ..Default::default()
+        };
+        let canvas = render_cursor(&mut load(), 0.5, &mut escape, (64, 64));
+        assert!(canvas.pixel(32, 32).a > 0);
+    }
+}
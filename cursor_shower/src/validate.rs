@@ -0,0 +1,327 @@
+//! Catches mistakes in a cursor that deserialize fine but would misbehave once it is running.
+
+use std::collections::HashSet;
+
+use crate::{
+    diagnostics::{JsonPath, PathSegment},
+    gen_shape::{Drawable, Shape},
+    input::MOUSE_BUTTON_COUNT,
+    linear_samplers::{Movesampler1D, Sampler1D},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    pub path: JsonPath,
+    pub message: String,
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "at {}: {}", self.path, self.message)
+    }
+}
+
+/// Every sampler in the cursor, with its path from the root of the file.
+fn samplers(cursor: &[Shape]) -> Vec<(Vec<PathSegment>, &Movesampler1D)> {
+    let mut samplers = Vec::new();
+    let mut pending: Vec<_> = cursor
+        .iter()
+        .enumerate()
+        .flat_map(|(i, shape)| {
+            shape
+                .children()
+                .into_iter()
+                .map(move |(path, sampler)| ([vec![PathSegment::Index(i)], path].concat(), sampler))
+        })
+        .collect();
+    pending.reverse();
+
+    while let Some((path, sampler)) = pending.pop() {
+        for (child_path, child) in sampler.children().into_iter().rev() {
+            pending.push(([&path[..], &child_path].concat(), child));
+        }
+        samplers.push((path, sampler));
+    }
+    samplers
+}
+
+fn check_button(button: usize, field: &str, path: &[PathSegment], problems: &mut Vec<Problem>) {
+    if button == 0 || button >= MOUSE_BUTTON_COUNT {
+        problems.push(Problem {
+            path: JsonPath([path, &[PathSegment::Key(field.to_string())]].concat()),
+            message: format!(
+                "mouse button {} does not exist, expected 1 to {}",
+                button,
+                MOUSE_BUTTON_COUNT - 1
+            ),
+        });
+    }
+}
+
+fn check_divisor(
+    divisor: &Movesampler1D,
+    field: &str,
+    path: &[PathSegment],
+    problems: &mut Vec<Problem>,
+) {
+    if matches!(divisor, Movesampler1D::Constant(c) if *c == 0.0) {
+        problems.push(Problem {
+            path: JsonPath([path, &[PathSegment::Key(field.to_string())]].concat()),
+            message: "divides by zero".to_string(),
+        });
+    }
+}
+
+/// Lists everything wrong with `cursor`, in file order. An empty list means it is fine to run.
+pub fn validate(cursor: &[Shape]) -> Vec<Problem> {
+    let samplers = samplers(cursor);
+    let set_variables: HashSet<&str> = samplers
+        .iter()
+        .filter_map(|(_, sampler)| match sampler {
+            Movesampler1D::VariableSet(set) => Some(set.set_variable_name.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    let mut problems = Vec::new();
+    for (path, sampler) in &samplers {
+        match sampler {
+            Movesampler1D::KeyChord(chord) if chord.keys.is_empty() => problems.push(Problem {
+                path: JsonPath(path.clone()),
+                message: "`KeyChord` has no keys".to_string(),
+            }),
+            Movesampler1D::KeySequence(sequence) if sequence.keys.is_empty() => {
+                problems.push(Problem {
+                    path: JsonPath(path.clone()),
+                    message: "`KeySequence` has no keys".to_string(),
+                })
+            }
+            Movesampler1D::MouseClick(click) => {
+                check_button(click.mouse_button, "mouse_button", path, &mut problems);
+                match (&click.mouse_timer_decrease, &click.envelope) {
+                    (None, None) => problems.push(Problem {
+                        path: JsonPath(path.clone()),
+                        message: "needs either `mouse_timer_decrease` or `envelope`".to_string(),
+                    }),
+                    (Some(_), Some(_)) => problems.push(Problem {
+                        path: JsonPath(path.clone()),
+                        message: "has both `mouse_timer_decrease` and `envelope`, \
+                                  use only one of them"
+                            .to_string(),
+                    }),
+                    _ => {}
+                }
+            }
+            Movesampler1D::MultiClick(multi_click) => {
+                check_button(
+                    multi_click.mouse_button,
+                    "mouse_button",
+                    path,
+                    &mut problems,
+                );
+                if multi_click.count == 0 {
+                    problems.push(Problem {
+                        path: JsonPath(
+                            [&path[..], &[PathSegment::Key("count".to_string())]].concat(),
+                        ),
+                        message: "`count` is 0, it must be at least 1".to_string(),
+                    });
+                }
+            }
+            Movesampler1D::ClickRate(click_rate) => {
+                check_button(click_rate.mouse_button, "mouse_button", path, &mut problems);
+                if click_rate.window <= 0.0 {
+                    problems.push(Problem {
+                        path: JsonPath(
+                            [&path[..], &[PathSegment::Key("window".to_string())]].concat(),
+                        ),
+                        message: format!("`window` is {}, it must be above 0", click_rate.window),
+                    });
+                }
+            }
+            Movesampler1D::ScrollValue(scroll) if scroll.min > scroll.max => {
+                problems.push(Problem {
+                    path: JsonPath(path.clone()),
+                    message: format!("`min` ({}) is above `max` ({})", scroll.min, scroll.max),
+                })
+            }
+            Movesampler1D::MouseClickCounter(counter) => check_button(
+                counter.mouse_click_counter_button,
+                "mouse_click_counter_button",
+                path,
+                &mut problems,
+            ),
+            Movesampler1D::VariableGet(get)
+                if !set_variables.contains(get.variable_name.as_str()) =>
+            {
+                problems.push(Problem {
+                    path: JsonPath(path.clone()),
+                    message: format!(
+                        "variable `{}` is never set by a VariableSet",
+                        get.variable_name
+                    ),
+                });
+            }
+            Movesampler1D::Divide(divide) => {
+                check_divisor(&divide.bottom, "bottom", path, &mut problems)
+            }
+            Movesampler1D::Modulo(modulo) => {
+                check_divisor(&modulo.divisor, "divisor", path, &mut problems)
+            }
+            Movesampler1D::Map(map) if map.before_min == map.before_max => {
+                problems.push(Problem {
+                    path: JsonPath(path.clone()),
+                    message: format!(
+                        "`before_min` and `before_max` are both {}, so the range is empty",
+                        map.before_min
+                    ),
+                });
+            }
+            Movesampler1D::Ease(ease) if ease.before_min == ease.before_max => {
+                problems.push(Problem {
+                    path: JsonPath(path.clone()),
+                    message: format!(
+                        "`before_min` and `before_max` are both {}, so the range is empty",
+                        ease.before_min
+                    ),
+                });
+            }
+            Movesampler1D::Keyframes(keyframes) => {
+                if keyframes.keyframes.is_empty() {
+                    problems.push(Problem {
+                        path: JsonPath(path.clone()),
+                        message: "`Keyframes` has no keyframes".to_string(),
+                    });
+                }
+                for (i, pair) in keyframes.keyframes.windows(2).enumerate() {
+                    if pair[1].time < pair[0].time {
+                        problems.push(Problem {
+                            path: JsonPath(
+                                [
+                                    &path[..],
+                                    &[
+                                        PathSegment::Key("keyframes".to_string()),
+                                        PathSegment::Index(i + 1),
+                                    ],
+                                ]
+                                .concat(),
+                            ),
+                            message: format!(
+                                "keyframe at {} comes after one at {}, times must increase",
+                                pair[1].time, pair[0].time
+                            ),
+                        });
+                    }
+                }
+            }
+            Movesampler1D::Min(min) if min.values.is_empty() => problems.push(Problem {
+                path: JsonPath(path.clone()),
+                message: "`Min` has no values".to_string(),
+            }),
+            Movesampler1D::Max(max) if max.values.is_empty() => problems.push(Problem {
+                path: JsonPath(path.clone()),
+                message: "`Max` has no values".to_string(),
+            }),
+            Movesampler1D::Clamp(clamp) => {
+                if let (Movesampler1D::Constant(min), Movesampler1D::Constant(max)) =
+                    (&*clamp.min, &*clamp.max)
+                {
+                    if min > max {
+                        problems.push(Problem {
+                            path: JsonPath(path.clone()),
+                            message: format!("`min` ({}) is above `max` ({})", min, max),
+                        });
+                    }
+                }
+            }
+            Movesampler1D::Spring(spring) if spring.mass <= 0.0 => problems.push(Problem {
+                path: JsonPath([&path[..], &[PathSegment::Key("mass".to_string())]].concat()),
+                message: format!("`mass` is {}, it must be above 0", spring.mass),
+            }),
+            _ => {}
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_runtime_problems() {
+        let cursor: Vec<Shape> = serde_jsonrc::from_str(
+            r#"[
+  {
+    "enabled": { "keys": ["B", "Escape"] },
+    "movement": [
+      {
+        "type": "Offset",
+        "x": { "top": 1.0, "bottom": 0.0 },
+        "y": { "mouse_timer_decrease": 0.1, "mouse_button": 6, "force_full_cycle": false }
+      }
+    ],
+    "shape": {
+      "type": "Rect",
+      "width": {
+        "before_min": 1.0, "before_max": 1.0, "after_min": 0.0, "after_max": 1.0,
+        "sampler": { "variable_name": "mode" }
+      },
+      "height": {
+        "type": "Add",
+        "terms": [
+          { "base": { "variable_name": "size" }, "divisor": 0.0 },
+          { "type": "Clamp", "value": 1.0, "min": 2.0, "max": 1.0 },
+          { "type": "Max", "values": [] },
+          { "type": "Spring", "target": 1.0, "mass": 0.0 },
+          { "mouse_button": 1, "force_full_cycle": false },
+          {
+            "mouse_button": 1, "force_full_cycle": false, "mouse_timer_decrease": 0.1,
+            "envelope": { "release": 0.1 }
+          },
+          { "type": "ClickRate", "mouse_button": 2, "window": 0.0 },
+          { "type": "KeySequence", "keys": [] },
+          { "type": "MultiClick", "mouse_button": 1, "count": 0 }
+        ]
+      },
+      "color_tl": { "r": 0, "g": 0, "b": 0, "a": 0 },
+      "color_tr": { "r": 0, "g": 0, "b": 0, "a": 0 },
+      "color_bl": { "r": 0, "g": 0, "b": 0, "a": 0 },
+      "color_br": { "r": 0, "g": 0, "b": 0, "a": 0 }
+    }
+  },
+  { "enabled": { "set_variable_name": "size", "value": 1.0 }, "movement": [], "shape": { "type": "NoDraw" } }
+]"#,
+        )
+        .unwrap();
+
+        let problems: Vec<String> = validate(&cursor).iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            problems,
+            [
+                "at [0].movement[0].x.bottom: divides by zero",
+                "at [0].movement[0].y.mouse_button: mouse button 6 does not exist, expected 1 to 5",
+                "at [0].shape.width: `before_min` and `before_max` are both 1, so the range is empty",
+                "at [0].shape.width.sampler: variable `mode` is never set by a VariableSet",
+                "at [0].shape.height.terms[0].divisor: divides by zero",
+                "at [0].shape.height.terms[1]: `min` (2) is above `max` (1)",
+                "at [0].shape.height.terms[2]: `Max` has no values",
+                "at [0].shape.height.terms[3].mass: `mass` is 0, it must be above 0",
+                "at [0].shape.height.terms[4]: needs either `mouse_timer_decrease` or `envelope`",
+                "at [0].shape.height.terms[5]: has both `mouse_timer_decrease` and `envelope`, use only one of them",
+                "at [0].shape.height.terms[6].window: `window` is 0, it must be above 0",
+                "at [0].shape.height.terms[7]: `KeySequence` has no keys",
+                "at [0].shape.height.terms[8].count: `count` is 0, it must be at least 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn example_cursors_are_clean() {
+        for name in ["myCursor", "spinningDots"] {
+            let file = std::fs::File::open(format!("../cursors/{}.jsonc", name)).unwrap();
+            let cursor: Vec<Shape> = serde_jsonrc::from_reader(file).unwrap();
+            assert_eq!(validate(&cursor), [], "{}", name);
+        }
+    }
+}
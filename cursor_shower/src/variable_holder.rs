@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+#[derive(Default)]
 pub struct DataHolder {
     pub key_data: HashMap<String, usize>,
     pub key_data_vec: Vec<f32>,
@@ -34,4 +35,18 @@ impl DataHolder {
     pub fn get(&self, key: usize) -> f32 {
         self.key_data_vec[key]
     }
+
+    /// Every named variable and its current value, in slot order.
+    pub fn snapshot(&self) -> Vec<(&str, f32)> {
+        let mut names: Vec<(&str, usize)> = self
+            .key_data
+            .iter()
+            .map(|(name, slot)| (name.as_str(), *slot))
+            .collect();
+        names.sort_by_key(|(_, slot)| *slot);
+        names
+            .into_iter()
+            .map(|(name, slot)| (name, self.key_data_vec[slot]))
+            .collect()
+    }
 }
@@ -179,7 +179,7 @@ pub fn quad_in_out(t: f32, b: f32, c: f32, d: f32) -> f32 {
     if td < 1.0 {
         ((c / 2.0) * (td * td)) + b
     } else {
-        -c / 2.0 * (((td - 2.0) * (td - 1.0)) - 1.0) + b
+        -c / 2.0 * (((td - 1.0) * (td - 3.0)) - 1.0) + b
     }
 }
 
@@ -208,9 +208,9 @@ pub fn expo_in_out(t: f32, b: f32, c: f32, d: f32) -> f32 {
 
     let td = t / (d / 2.0);
     if td < 1.0 {
-        return c / 2.0 * 2.0f32.powf(10.0 * (t - 1.0)) + b;
+        return c / 2.0 * 2.0f32.powf(10.0 * (td - 1.0)) + b;
     } else {
-        return c / 2.0 * (-(2.0f32.powf(-10.0 * td - 1.0)) + 2.0) + b;
+        return c / 2.0 * (-(2.0f32.powf(-10.0 * (td - 1.0))) + 2.0) + b;
     }
 }
 
@@ -233,9 +233,9 @@ pub fn back_in_out(t: f32, b: f32, c: f32, d: f32) -> f32 {
         s *= 1.525;
         c / 2.0 * (td * td * ((s + 1.0) * td - s)) + b
     } else {
-        let postfix = t - 2.0;
+        let postfix = td - 2.0;
         s *= 1.525;
-        c / 2.0 * ((postfix) * postfix * ((s + 1.0) * t + s) + 2.0) + b
+        c / 2.0 * ((postfix) * postfix * ((s + 1.0) * postfix + s) + 2.0) + b
     }
 }
 